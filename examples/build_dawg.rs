@@ -0,0 +1,37 @@
+use std::env;
+use std::fs::{self, File};
+use std::io::BufWriter;
+
+use scrabble::{Alphabet, DawgBuilder};
+
+/// Builds a dawg from a word list with one word per line, recording the lexicon's name and date
+/// in its header, e.g. `cargo run --release --example build_dawg assets/scrabble_words.txt
+/// assets/dawg.bin scrabble_words 2020-02-23`
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 3 || args.len() > 5 {
+        eprintln!(
+            "Usage: {} <word list> <output dawg> [lexicon name] [date]",
+            args[0]
+        );
+        std::process::exit(1);
+    }
+    let words = fs::read_to_string(&args[1]).expect("Couldn't read the word list");
+
+    let mut builder = DawgBuilder::new(Alphabet::english());
+    builder
+        .add_words(words.lines().map(str::trim))
+        .expect("Invalid word");
+    let mut dawg = builder.build();
+    dawg.metadata.lexicon = args.get(3).cloned().unwrap_or_default();
+    dawg.metadata.date = args.get(4).cloned().unwrap_or_default();
+
+    let mut writer = BufWriter::new(File::create(&args[2]).expect("Couldn't create the dawg"));
+    dawg.write_to(&mut writer).expect("Couldn't write the dawg");
+    println!(
+        "Wrote {} words in {} edges to {}",
+        dawg.metadata.word_count,
+        dawg.n_edges(),
+        args[2]
+    );
+}
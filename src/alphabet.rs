@@ -0,0 +1,295 @@
+use std::error::Error;
+use std::fmt;
+
+/// Cross checks hold one bit per letter in a `u64`
+pub const MAX_LETTERS: usize = 64;
+pub const BLANK_TILE_CHAR: char = '_';
+
+const ENGLISH_LETTERS: [&str; 26] = [
+    "a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l", "m", "n", "o", "p", "q", "r", "s",
+    "t", "u", "v", "w", "x", "y", "z",
+];
+
+const SPANISH_LETTERS: [&str; 28] = [
+    "a", "b", "c", "ch", "d", "e", "f", "g", "h", "i", "j", "l", "ll", "m", "n", "ñ", "o", "p",
+    "q", "r", "rr", "s", "t", "u", "v", "x", "y", "z",
+];
+
+/// Stand-in symbols for tiles whose spelling is more than one character. They need upper and
+/// lowercase forms so that blanks can be recorded the same way as for any other letter.
+const MULTI_CHAR_SYMBOLS: &str = "αβγδεζηθικλμνξοπρστυφχψω";
+
+/// The index of a letter in its alphabet
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct Letter(pub u8);
+
+impl Letter {
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// The letters of a language, in the order used for their indices.
+///
+/// Every tile is stored as a single lowercase `char` on the board, on racks and in plays, with
+/// blanks in uppercase. A tile whose spelling is a single character is stored as itself, and one
+/// with a longer spelling, like Spanish "ch", as a stand-in symbol; `parse` and `spell` convert
+/// between text and stored tiles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alphabet {
+    spellings: Vec<String>,
+    symbols: Vec<char>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlphabetError {
+    Empty,
+    TooManyLetters(usize),
+    DuplicateLetter(String),
+    /// Letters must be lowercase and have a single-character uppercase form for blanks
+    InvalidLetter(String),
+    /// The text couldn't be split into tiles of the alphabet
+    UnknownTile(String),
+}
+
+impl fmt::Display for AlphabetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AlphabetError::Empty => write!(f, "The alphabet has no letters"),
+            AlphabetError::TooManyLetters(n) => write!(
+                f,
+                "The alphabet has {} letters, but at most {} are supported",
+                n, MAX_LETTERS
+            ),
+            AlphabetError::DuplicateLetter(letter) => write!(f, "Duplicate letter '{}'", letter),
+            AlphabetError::InvalidLetter(letter) => write!(f, "Invalid letter '{}'", letter),
+            AlphabetError::UnknownTile(text) => write!(f, "No tile matches '{}'", text),
+        }
+    }
+}
+
+impl Error for AlphabetError {}
+
+impl Alphabet {
+    /// Builds an alphabet from the lowercase spelling of each letter
+    pub fn new(spellings: &[&str]) -> Result<Alphabet, AlphabetError> {
+        if spellings.is_empty() {
+            return Err(AlphabetError::Empty);
+        }
+        if spellings.len() > MAX_LETTERS {
+            return Err(AlphabetError::TooManyLetters(spellings.len()));
+        }
+        let mut stand_ins = MULTI_CHAR_SYMBOLS
+            .chars()
+            .filter(|symbol| !spellings.contains(&symbol.to_string().as_str()));
+        let mut symbols = Vec::with_capacity(spellings.len());
+        for (index, &spelling) in spellings.iter().enumerate() {
+            if spellings[..index].contains(&spelling) {
+                return Err(AlphabetError::DuplicateLetter(spelling.to_string()));
+            }
+            if spelling.is_empty() || !spelling.chars().all(has_blank_form) {
+                return Err(AlphabetError::InvalidLetter(spelling.to_string()));
+            }
+            let mut chars = spelling.chars();
+            let symbol = match (chars.next(), chars.next()) {
+                (Some(ch), None) => ch,
+                _ => stand_ins
+                    .next()
+                    .ok_or(AlphabetError::TooManyLetters(spellings.len()))?,
+            };
+            symbols.push(symbol);
+        }
+        Ok(Alphabet {
+            spellings: spellings
+                .iter()
+                .map(|spelling| spelling.to_string())
+                .collect(),
+            symbols,
+        })
+    }
+
+    pub fn english() -> Alphabet {
+        Alphabet::new(&ENGLISH_LETTERS).unwrap()
+    }
+
+    /// The Spanish alphabet, including the CH, LL and RR tiles
+    pub fn spanish() -> Alphabet {
+        Alphabet::new(&SPANISH_LETTERS).unwrap()
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// Every letter, one bit per letter as in cross checks
+    pub fn all_letters(&self) -> u64 {
+        u64::MAX
+            .checked_shr((MAX_LETTERS - self.len()) as u32)
+            .unwrap_or(0)
+    }
+
+    pub fn letters(&self) -> impl Iterator<Item = Letter> {
+        (0..self.symbols.len() as u8).map(Letter)
+    }
+
+    /// The letter a stored tile stands for, whether or not it was played from a blank
+    pub fn letter(&self, tile: char) -> Option<Letter> {
+        let symbol = to_lowercase(tile);
+        self.symbols
+            .iter()
+            .position(|&other| other == symbol)
+            .map(|index| Letter(index as u8))
+    }
+
+    /// The lowercase character `letter` is stored as
+    pub fn symbol(&self, letter: Letter) -> char {
+        self.symbols[letter.index()]
+    }
+
+    pub fn spelling(&self, letter: Letter) -> &str {
+        &self.spellings[letter.index()]
+    }
+
+    /// Reads the first tile of `text`, returning it as stored along with the number of bytes of
+    /// `text` it was written with. The longest matching spelling wins, a spelling written in
+    /// uppercase is a blank, and `_` is an undesignated blank.
+    pub fn next_tile(&self, text: &str) -> Option<(char, usize)> {
+        if text.starts_with(BLANK_TILE_CHAR) {
+            return Some((BLANK_TILE_CHAR, BLANK_TILE_CHAR.len_utf8()));
+        }
+        let mut best: Option<(char, usize, usize)> = None;
+        for (spelling, &symbol) in self.spellings.iter().zip(self.symbols.iter()) {
+            let n_chars = spelling.chars().count();
+            if best.is_some_and(|(_, _, best_chars)| best_chars >= n_chars) {
+                continue;
+            }
+            let prefix: String = text.chars().take(n_chars).collect();
+            if prefix.chars().count() < n_chars || prefix.to_lowercase() != *spelling {
+                continue;
+            }
+            let tile = if prefix.chars().all(char::is_uppercase) {
+                to_uppercase(symbol)
+            } else {
+                symbol
+            };
+            best = Some((tile, prefix.len(), n_chars));
+        }
+        best.map(|(tile, n_bytes, _)| (tile, n_bytes))
+    }
+
+    /// Splits written text into stored tiles, e.g. "cHorro" into the tiles c, H, o, rr, o
+    /// of the Spanish alphabet
+    pub fn parse(&self, text: &str) -> Result<String, AlphabetError> {
+        let mut tiles = String::with_capacity(text.len());
+        let mut rest = text;
+        while !rest.is_empty() {
+            let (tile, n_bytes) = self
+                .next_tile(rest)
+                .ok_or_else(|| AlphabetError::UnknownTile(rest.to_string()))?;
+            tiles.push(tile);
+            rest = &rest[n_bytes..];
+        }
+        Ok(tiles)
+    }
+
+    /// Writes out stored tiles, with blanks in uppercase. Characters that aren't tiles of the
+    /// alphabet are kept as they are.
+    pub fn spell(&self, tiles: &str) -> String {
+        let mut text = String::with_capacity(tiles.len());
+        for tile in tiles.chars() {
+            match self.letter(tile) {
+                Some(letter) if tile.is_uppercase() => {
+                    text.push_str(&self.spelling(letter).to_uppercase())
+                }
+                Some(letter) => text.push_str(self.spelling(letter)),
+                None => text.push(tile),
+            }
+        }
+        text
+    }
+}
+
+impl Default for Alphabet {
+    fn default() -> Alphabet {
+        Alphabet::english()
+    }
+}
+
+pub fn to_uppercase(ch: char) -> char {
+    ch.to_uppercase().next().unwrap_or(ch)
+}
+
+pub fn to_lowercase(ch: char) -> char {
+    ch.to_lowercase().next().unwrap_or(ch)
+}
+
+/// Whether `ch` is a lowercase letter that round-trips through a single uppercase character
+fn has_blank_form(ch: char) -> bool {
+    let mut upper = ch.to_uppercase();
+    ch.is_lowercase()
+        && upper.len() == 1
+        && upper
+            .next()
+            .is_some_and(|upper| upper != ch && to_lowercase(upper) == ch)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_english() {
+        let english = Alphabet::english();
+        assert_eq!(english.len(), 26);
+        assert_eq!(english.letter('a'), Some(Letter(0)));
+        assert_eq!(english.letter('Z'), Some(Letter(25)));
+        assert_eq!(english.letter('?'), None);
+        assert_eq!(english.parse("heLlo"), Ok("heLlo".to_string()));
+    }
+
+    #[test]
+    fn test_multi_char_tiles() {
+        let spanish = Alphabet::spanish();
+        let tiles = spanish.parse("chorro").unwrap();
+        assert_eq!(tiles.chars().count(), 4);
+        let ch = spanish.letter(tiles.chars().next().unwrap()).unwrap();
+        assert_eq!(spanish.spelling(ch), "ch");
+        assert_eq!(spanish.spell(&tiles), "chorro");
+
+        let blanks = spanish.parse("CHoRRo_").unwrap();
+        assert_eq!(blanks.chars().count(), 5);
+        assert_eq!(spanish.spell(&blanks), "CHoRRo_");
+        assert_eq!(spanish.parse("ñu").unwrap(), "ñu");
+        assert_eq!(
+            spanish.parse("kilo"),
+            Err(AlphabetError::UnknownTile("kilo".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_invalid_alphabets() {
+        assert_eq!(Alphabet::new(&[]), Err(AlphabetError::Empty));
+        assert_eq!(
+            Alphabet::new(&["a", "b", "a"]),
+            Err(AlphabetError::DuplicateLetter("a".to_string()))
+        );
+        assert_eq!(
+            Alphabet::new(&["a", "B"]),
+            Err(AlphabetError::InvalidLetter("B".to_string()))
+        );
+        assert_eq!(
+            Alphabet::new(&["a", "1"]),
+            Err(AlphabetError::InvalidLetter("1".to_string()))
+        );
+        let too_many: Vec<String> = (0..65).map(|i| format!("a{}", "b".repeat(i))).collect();
+        let too_many: Vec<&str> = too_many.iter().map(String::as_str).collect();
+        assert_eq!(
+            Alphabet::new(&too_many),
+            Err(AlphabetError::TooManyLetters(65))
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+
+use crate::alphabet::BLANK_TILE_CHAR;
+use crate::dawg::{Dawg, DawgNodeIndex};
+use crate::game::{placed_tile, ScrabbleRack};
+
+/// Words by their number of tiles
+pub type AnagramsByLength = BTreeMap<usize, Vec<Anagram>>;
+
+/// A word that can be made from a rack, with the letters taken from blanks in uppercase
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Anagram {
+    pub word: String,
+}
+
+impl Anagram {
+    /// The letters the blanks stand for, in the order they appear in the word
+    pub fn blanks(&self) -> String {
+        self.word
+            .chars()
+            .filter(|tile| tile.is_uppercase())
+            .flat_map(char::to_lowercase)
+            .collect()
+    }
+}
+
+impl<'a> Dawg<'a> {
+    /// The words that use every tile of `rack`
+    pub fn anagrams(&self, rack: &ScrabbleRack) -> AnagramsByLength {
+        self.subanagrams(rack, rack.len())
+    }
+
+    /// The words of at least `min_len` tiles that can be made from `rack`. A blank stands for a
+    /// letter only once the rack's own tiles of that letter have run out, so "baa" from "ba_"
+    /// is found once, as "baA".
+    pub fn subanagrams(&self, rack: &ScrabbleRack, min_len: usize) -> AnagramsByLength {
+        let mut search = AnagramSearch {
+            dawg: self,
+            min_len: min_len.max(1),
+            rack: rack.clone(),
+            word: String::new(),
+            n_tiles: 0,
+            found: BTreeMap::new(),
+        };
+        search.extend(self.root());
+        search.found
+    }
+}
+
+struct AnagramSearch<'d, 'a> {
+    dawg: &'d Dawg<'a>,
+    min_len: usize,
+    rack: ScrabbleRack,
+    word: String,
+    n_tiles: usize,
+    found: AnagramsByLength,
+}
+
+impl<'d, 'a> AnagramSearch<'d, 'a> {
+    fn extend(&mut self, node: DawgNodeIndex) {
+        if node.is_null() || self.rack.is_empty() {
+            return;
+        }
+        let dawg = self.dawg;
+        dawg.apply_to_child_edges(node, |edge| {
+            let letter = dawg.alphabet.symbol(edge.letter);
+            let tile = if self.rack.remove_letter(edge.letter) {
+                letter
+            } else if self.rack.remove_blank() {
+                BLANK_TILE_CHAR
+            } else {
+                return;
+            };
+            self.word.push(placed_tile(tile, letter));
+            self.n_tiles += 1;
+            if edge.word_terminator && self.n_tiles >= self.min_len {
+                self.found.entry(self.n_tiles).or_default().push(Anagram {
+                    word: self.word.clone(),
+                });
+            }
+            self.extend(edge.target);
+            self.n_tiles -= 1;
+            self.word.pop();
+            match tile {
+                BLANK_TILE_CHAR => self.rack.add_blank(),
+                _ => self.rack.add_letter(edge.letter),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::loading::load_dawg;
+
+    fn words(anagrams: &[Anagram]) -> Vec<&str> {
+        anagrams
+            .iter()
+            .map(|anagram| anagram.word.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_anagrams() {
+        let dawg = load_dawg();
+        let anagrams = dawg.anagrams(&ScrabbleRack::new("retains"));
+        assert_eq!(anagrams.keys().collect::<Vec<_>>(), vec![&7]);
+        let found = words(&anagrams[&7]);
+        for word in ["nastier", "retains", "retinas", "stainer"] {
+            assert!(found.contains(&word), "{}", word);
+        }
+
+        let subanagrams = dawg.subanagrams(&ScrabbleRack::new("cat"), 2);
+        assert_eq!(words(&subanagrams[&2]), vec!["at", "ta"]);
+        assert_eq!(words(&subanagrams[&3]), vec!["act", "cat"]);
+    }
+
+    #[test]
+    fn test_anagrams_with_blanks() {
+        let dawg = load_dawg();
+        let anagrams = dawg.anagrams(&ScrabbleRack::new("ca_"));
+        let found = &anagrams[&3];
+        assert!(words(found).contains(&"caT"));
+        assert!(words(found).contains(&"aRc"));
+        // The blank isn't used for a letter the rack already has
+        assert!(!words(found).contains(&"cAt"));
+        let cat = found.iter().find(|anagram| anagram.word == "caT").unwrap();
+        assert_eq!(cat.blanks(), "t");
+        assert!(found.iter().all(|anagram| anagram.blanks().len() == 1));
+
+        // Held copies of a letter are used up before the blank
+        let baa = dawg.anagrams(&ScrabbleRack::new("ba_"));
+        let baa: Vec<&str> = words(&baa[&3])
+            .into_iter()
+            .filter(|word| word.eq_ignore_ascii_case("baa"))
+            .collect();
+        assert_eq!(baa, vec!["baA"]);
+    }
+}
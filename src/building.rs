@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+
+use crate::alphabet::{Alphabet, AlphabetError, Letter};
+use crate::dawg::{Dawg, DawgEdge, DawgNodeIndex, MAX_DAWG_EDGES, NULL_DAWG_NODE_INDEX};
+
+/// An edge of a minimised node: its letter, whether it completes a word, and the index of the
+/// block of edges it leads to, if any
+type BlockEdge = (Letter, bool, Option<usize>);
+
+#[derive(Default)]
+struct TrieNode {
+    word_terminator: bool,
+    /// Sorted by letter
+    children: Vec<(Letter, usize)>,
+}
+
+/// Builds a minimised `Dawg` from a list of words, in any order.
+///
+/// Words are first added to a trie, then nodes with identical outgoing edges are merged. The
+/// word terminator is stored on the edge leading into a node, so two nodes only need the same
+/// edges, not the same terminator, to be merged.
+pub struct DawgBuilder {
+    alphabet: Alphabet,
+    nodes: Vec<TrieNode>,
+}
+
+impl DawgBuilder {
+    pub fn new(alphabet: Alphabet) -> DawgBuilder {
+        DawgBuilder {
+            alphabet,
+            nodes: vec![TrieNode::default()],
+        }
+    }
+
+    /// Adds a word written as in `Alphabet::parse`; empty words are ignored
+    pub fn add_word(&mut self, word: &str) -> Result<(), AlphabetError> {
+        let tiles = self.alphabet.parse(word)?;
+        if tiles.is_empty() {
+            return Ok(());
+        }
+        let letters = tiles
+            .chars()
+            .map(|tile| self.alphabet.letter(tile))
+            .collect::<Option<Vec<Letter>>>()
+            .ok_or_else(|| AlphabetError::UnknownTile(word.to_string()))?;
+        self.add_letters(&letters);
+        Ok(())
+    }
+
+    /// Adds a non-empty sequence of letters, which needn't belong to the alphabet; this lets a
+    /// `Gaddag` store its separator as the letter after the last one
+    pub(crate) fn add_letters(&mut self, letters: &[Letter]) {
+        let mut node = 0;
+        for &letter in letters {
+            node = self.child(node, letter);
+        }
+        self.nodes[node].word_terminator = true;
+    }
+
+    pub fn add_words<I, S>(&mut self, words: I) -> Result<(), AlphabetError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for word in words {
+            self.add_word(word.as_ref())?;
+        }
+        Ok(())
+    }
+
+    /// Panics if the dawg would have more than `MAX_DAWG_EDGES` edges
+    pub fn build(&self) -> Dawg<'static> {
+        let mut blocks: Vec<Vec<BlockEdge>> = Vec::new();
+        let mut registry: HashMap<Vec<BlockEdge>, usize> = HashMap::new();
+        let root = self.minimise(0, &mut blocks, &mut registry);
+
+        let mut edges = Vec::new();
+        if let Some(root) = root {
+            // The root has to come first; every other block follows in breadth-first order
+            let mut starts: Vec<Option<u32>> = vec![None; blocks.len()];
+            let mut order = vec![root];
+            starts[root] = Some(0);
+            let mut n_edges = blocks[root].len();
+            let mut next = 0;
+            while next < order.len() {
+                for &(_, _, target) in blocks[order[next]].iter() {
+                    if let Some(target) = target {
+                        if starts[target].is_none() {
+                            starts[target] = Some(n_edges as u32);
+                            n_edges += blocks[target].len();
+                            order.push(target);
+                        }
+                    }
+                }
+                next += 1;
+            }
+
+            assert!(
+                n_edges <= MAX_DAWG_EDGES as usize,
+                "{} edges is too many for a dawg",
+                n_edges
+            );
+            edges.reserve(n_edges);
+            for &block in order.iter() {
+                let block_edges = &blocks[block];
+                for (i, &(letter, word_terminator, target)) in block_edges.iter().enumerate() {
+                    let target = target.and_then(|target| starts[target]);
+                    edges.push(DawgEdge {
+                        letter,
+                        word_terminator,
+                        node_terminator: i + 1 == block_edges.len(),
+                        target: DawgNodeIndex(target.unwrap_or(NULL_DAWG_NODE_INDEX)),
+                    });
+                }
+            }
+        }
+        let mut dawg = Dawg::from_edges(self.alphabet.clone(), &edges);
+        dawg.metadata.word_count = dawg.word_count();
+        dawg
+    }
+
+    fn child(&mut self, node: usize, letter: Letter) -> usize {
+        let children = &self.nodes[node].children;
+        match children.binary_search_by_key(&letter, |&(child_letter, _)| child_letter) {
+            Ok(index) => children[index].1,
+            Err(index) => {
+                let child = self.nodes.len();
+                self.nodes.push(TrieNode::default());
+                self.nodes[node].children.insert(index, (letter, child));
+                child
+            }
+        }
+    }
+
+    /// Registers the block of edges leaving `node`, after those of its descendants, returning its
+    /// index in `blocks`. Nodes without children have no block.
+    fn minimise(
+        &self,
+        node: usize,
+        blocks: &mut Vec<Vec<BlockEdge>>,
+        registry: &mut HashMap<Vec<BlockEdge>, usize>,
+    ) -> Option<usize> {
+        let children = &self.nodes[node].children;
+        if children.is_empty() {
+            return None;
+        }
+        let block: Vec<BlockEdge> = children
+            .iter()
+            .map(|&(letter, child)| {
+                let word_terminator = self.nodes[child].word_terminator;
+                (
+                    letter,
+                    word_terminator,
+                    self.minimise(child, blocks, registry),
+                )
+            })
+            .collect();
+        if let Some(&index) = registry.get(&block) {
+            return Some(index);
+        }
+        blocks.push(block.clone());
+        registry.insert(block, blocks.len() - 1);
+        Some(blocks.len() - 1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn build(words: &[&str]) -> Dawg<'static> {
+        let mut builder = DawgBuilder::new(Alphabet::english());
+        builder.add_words(words).unwrap();
+        builder.build()
+    }
+
+    #[test]
+    fn test_build() {
+        let dawg = build(&["cello", "hello", "hell", "jello", "cell", "abc", "ab", "bc"]);
+        for word in ["cello", "hello", "hell", "jello", "cell", "abc", "ab", "bc"] {
+            assert!(dawg.contains(word), "{}", word);
+        }
+        for word in ["b", "jell", "hel", "helloe", "a", "cellos"] {
+            assert!(!dawg.contains(word), "{}", word);
+        }
+        // "c" and "h" share everything after their first letter, but "j" has no "jell"
+        let mut root_targets = Vec::new();
+        dawg.apply_to_child_edges(dawg.root(), |edge| root_targets.push(edge.target));
+        assert_eq!(root_targets[2], root_targets[3]);
+        assert_ne!(root_targets[3], root_targets[4]);
+    }
+
+    #[test]
+    fn test_order_does_not_matter() {
+        let sorted = build(&["ab", "abc", "b", "bc"]);
+        let unsorted = build(&["bc", "abc", "b", "ab"]);
+        assert_eq!(sorted.as_bytes(), unsorted.as_bytes());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dawg = build(&["cello", "hello", "hell", "jello", "zzz"]);
+        let mut bytes = Vec::new();
+        dawg.write_to(&mut bytes).unwrap();
+        assert!(bytes.ends_with(dawg.as_bytes()));
+        assert!(Dawg::from_bytes(&bytes).unwrap().edges().eq(dawg.edges()));
+    }
+
+    #[test]
+    fn test_multi_char_tiles() {
+        let alphabet = Alphabet::spanish();
+        let mut builder = DawgBuilder::new(alphabet.clone());
+        builder.add_words(["chorro", "carro", "perro"]).unwrap();
+        let dawg = builder.build();
+        assert!(dawg.contains(&alphabet.parse("chorro").unwrap()));
+        assert!(dawg.contains(&alphabet.parse("PeRRo").unwrap()));
+        assert!(!dawg.contains(&alphabet.parse("corro").unwrap()));
+        assert!(builder.add_word("kilo").is_err());
+    }
+}
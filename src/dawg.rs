@@ -1,10 +1,22 @@
-use std::ops::{Index, RangeFrom};
+use std::borrow::Cow;
+use std::convert::TryInto;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::iter;
+use std::mem::size_of;
+use std::path::Path;
+
+use crate::alphabet::{Alphabet, AlphabetError, Letter, MAX_LETTERS};
+use crate::building::DawgBuilder;
+use crate::header::{checksum, DawgHeader, DawgMetadata};
 
 /// Note: A DawgNode is really just the first DawgEdge in a block associated with a specific node
 /// So A DawgNodeIndex is actually a pointer to a DawgEdge
 #[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq)]
 pub struct DawgNodeIndex(pub u32);
-const NULL_DAWG_NODE_INDEX: u32 = std::u32::MAX;
+pub const NULL_DAWG_NODE_INDEX: u32 = u32::MAX;
 
 impl DawgNodeIndex {
     pub fn is_null(&self) -> bool {
@@ -16,39 +28,296 @@ impl DawgNodeIndex {
     }
 }
 
+/// The edges of a dawg, packed into a little-endian `u32` each.
+///
+/// The edges are read straight from the packed data, which can be borrowed, e.g. from a
+/// memory-mapped file or the embedded lexicon, so a dawg is ready as soon as its data is checked.
+/// Files start with a `DawgHeader` giving the alphabet and the lexicon's metadata.
+///
+/// The edges of a node are sorted by letter, and after the edges each one has a little-endian
+/// `u64` mask of its letter and those of the edges after it in its node. So the mask at a node's
+/// index has one bit per child, a child's edge is found by counting the bits below its letter,
+/// and the children can be intersected with cross checks or a rack before visiting any of them.
+#[derive(Debug, Clone)]
+pub struct Dawg<'a> {
+    /// The alphabet the edge letters index into
+    pub alphabet: Alphabet,
+    pub metadata: DawgMetadata,
+    data: Cow<'a, [u8]>,
+    /// Where the edges start in `data`, after the header if it was read from a file
+    edges_start: usize,
+    n_edges: usize,
+}
+
 #[derive(Debug)]
-pub struct Dawg {
-    pub edges: Vec<DawgEdge>,
+pub enum DawgError {
+    /// The data doesn't start with the magic bytes of a dawg file
+    NotADawg,
+    UnsupportedVersion(u16),
+    /// The data ends before the header or the edges it promises
+    Truncated,
+    /// The data has this many bytes more than the header and edges
+    InvalidLength(usize),
+    /// The header's text isn't valid UTF-8
+    InvalidHeader,
+    InvalidAlphabet(AlphabetError),
+    /// The edges don't match the checksum in the header
+    ChecksumMismatch,
+    /// The edge at this index has a letter outside the alphabet
+    InvalidLetter(usize),
+    /// The edge at this index points past the end of the edges
+    InvalidTarget(usize),
+    /// The last edge doesn't end its node, so the node runs off the end of the edges
+    UnterminatedNode,
+    /// The edge at this index doesn't have a later letter than the edge before it in its node
+    UnsortedNode(usize),
+    /// The edge at this index leads back to a node it can be reached from
+    Cycle(usize),
+    /// The letter mask of the edge at this index doesn't match the letters of its node
+    InvalidLetterMask(usize),
+    InvalidWord(AlphabetError),
+    Io(io::Error),
+}
+
+impl fmt::Display for DawgError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DawgError::NotADawg => write!(f, "Not a dawg file"),
+            DawgError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported dawg format version {}", version)
+            }
+            DawgError::Truncated => write!(f, "The dawg file is truncated"),
+            DawgError::InvalidLength(len) => write!(f, "{} unexpected bytes after the edges", len),
+            DawgError::InvalidHeader => write!(f, "The dawg header is not valid UTF-8"),
+            DawgError::InvalidAlphabet(error) => write!(f, "Invalid alphabet: {}", error),
+            DawgError::ChecksumMismatch => write!(f, "The edges don't match their checksum"),
+            DawgError::InvalidLetter(edge) => write!(f, "Edge {} has an invalid letter", edge),
+            DawgError::InvalidTarget(edge) => write!(f, "Edge {} has an invalid target", edge),
+            DawgError::UnterminatedNode => write!(f, "The last node has no final edge"),
+            DawgError::UnsortedNode(edge) => {
+                write!(f, "Edge {} is out of order in its node", edge)
+            }
+            DawgError::Cycle(edge) => write!(f, "Edge {} leads back into a cycle", edge),
+            DawgError::InvalidLetterMask(edge) => {
+                write!(f, "Edge {} has the wrong letter mask", edge)
+            }
+            DawgError::InvalidWord(error) => write!(f, "Invalid word: {}", error),
+            DawgError::Io(error) => write!(f, "Couldn't read dawg: {}", error),
+        }
+    }
+}
+
+impl Error for DawgError {}
+
+impl From<io::Error> for DawgError {
+    fn from(error: io::Error) -> Self {
+        DawgError::Io(error)
+    }
+}
+
+impl From<AlphabetError> for DawgError {
+    fn from(error: AlphabetError) -> Self {
+        DawgError::InvalidWord(error)
+    }
 }
 
-impl Dawg {
+impl<'a> Dawg<'a> {
+    /// Borrows a dawg file written by `write_to`
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Dawg<'a>, DawgError> {
+        Dawg::from_data(Cow::Borrowed(bytes), true)
+    }
+
+    /// Borrows a dawg file without its checksum or `validate`, for data known to be valid such
+    /// as the embedded lexicon. The header and length are still read and checked.
+    #[cfg(any(test, feature = "embedded-lexicon"))]
+    pub(crate) fn from_bytes_unchecked(bytes: &'a [u8]) -> Result<Dawg<'a>, DawgError> {
+        Dawg::from_data(Cow::Borrowed(bytes), false)
+    }
+
+    fn from_data(data: Cow<'a, [u8]>, check_edges: bool) -> Result<Dawg<'a>, DawgError> {
+        let header = DawgHeader::read(&data)?;
+        let edges_end = header.len + header.n_edges * (EDGE_SIZE + MASK_SIZE);
+        if data.len() < edges_end {
+            return Err(DawgError::Truncated);
+        }
+        if data.len() > edges_end {
+            return Err(DawgError::InvalidLength(data.len() - edges_end));
+        }
+        if check_edges && checksum(&data[header.len..]) != header.checksum {
+            return Err(DawgError::ChecksumMismatch);
+        }
+        let dawg = Dawg {
+            alphabet: header.alphabet,
+            metadata: header.metadata,
+            data,
+            edges_start: header.len,
+            n_edges: header.n_edges,
+        };
+        if check_edges {
+            dawg.validate()?;
+        }
+        Ok(dawg)
+    }
+
+    /// Checks that walking the dawg can't panic or loop forever: every letter is in the alphabet,
+    /// the letters of each node are in order and match its masks, every target is an edge, every
+    /// node ends and no node can be reached from itself. Dawgs read from files are checked, apart from the
+    /// embedded lexicon, which a test checks instead. An empty dawg is valid, and its root has no
+    /// edges.
+    pub fn validate(&self) -> Result<(), DawgError> {
+        let n_edges = self.n_edges();
+        let mut previous: Option<DawgEdge> = None;
+        for (index, edge) in self.edges().enumerate() {
+            if edge.letter.index() >= self.alphabet.len() {
+                return Err(DawgError::InvalidLetter(index));
+            }
+            if edge.target.is_some() && edge.target.0 as usize >= n_edges {
+                return Err(DawgError::InvalidTarget(index));
+            }
+            if let Some(previous) = previous.filter(|previous| !previous.node_terminator) {
+                if previous.letter >= edge.letter {
+                    return Err(DawgError::UnsortedNode(index));
+                }
+            }
+            previous = Some(edge);
+        }
+        if n_edges > 0 && !self.edge(DawgNodeIndex(n_edges as u32 - 1)).node_terminator {
+            return Err(DawgError::UnterminatedNode);
+        }
+        let edges: Vec<DawgEdge> = self.edges().collect();
+        let masks = letter_masks(&edges);
+        if let Some(index) = (0..n_edges)
+            .find(|&index| self.child_letters(DawgNodeIndex(index as u32)) != masks[index])
+        {
+            return Err(DawgError::InvalidLetterMask(index));
+        }
+        self.check_acyclic()
+    }
+
+    /// A depth-first search from the root and every target, kept on the heap so that a hostile
+    /// dawg can't overflow the stack
+    fn check_acyclic(&self) -> Result<(), DawgError> {
+        #[derive(Clone, Copy, PartialEq, Eq)]
+        enum Visit {
+            New,
+            Open,
+            Done,
+        }
+        let mut visits = vec![Visit::New; self.n_edges()];
+        // The nodes being searched, with the next of their edges to follow
+        let mut stack: Vec<(usize, Option<usize>)> = Vec::new();
+        let targets = self.edges().map(|edge| edge.target);
+        for start in iter::once(self.root()).chain(targets) {
+            if start.is_null() || visits.get(start.0 as usize) != Some(&Visit::New) {
+                continue;
+            }
+            visits[start.0 as usize] = Visit::Open;
+            stack.push((start.0 as usize, Some(start.0 as usize)));
+            while let Some(frame) = stack.last_mut() {
+                let index = match frame.1 {
+                    Some(index) => index,
+                    None => {
+                        visits[frame.0] = Visit::Done;
+                        stack.pop();
+                        continue;
+                    }
+                };
+                let edge = self.edge(DawgNodeIndex(index as u32));
+                frame.1 = match edge.node_terminator {
+                    true => None,
+                    false => Some(index + 1),
+                };
+                if edge.target.is_null() {
+                    continue;
+                }
+                let target = edge.target.0 as usize;
+                match visits[target] {
+                    Visit::Open => return Err(DawgError::Cycle(index)),
+                    Visit::New => {
+                        visits[target] = Visit::Open;
+                        stack.push((target, Some(target)));
+                    }
+                    Visit::Done => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Packs `edges`, which may use letters past the end of `alphabet`, and their letter masks
+    /// without checking them. The edges of each node have to be sorted by letter.
+    pub(crate) fn from_edges(alphabet: Alphabet, edges: &[DawgEdge]) -> Dawg<'static> {
+        let mut data = Vec::with_capacity(edges.len() * (EDGE_SIZE + MASK_SIZE));
+        for edge in edges {
+            data.extend_from_slice(&u32::from(edge).to_le_bytes());
+        }
+        for mask in letter_masks(edges) {
+            data.extend_from_slice(&mask.to_le_bytes());
+        }
+        Dawg {
+            alphabet,
+            metadata: DawgMetadata::default(),
+            data: Cow::Owned(data),
+            edges_start: 0,
+            n_edges: edges.len(),
+        }
+    }
+
     pub fn root(&self) -> DawgNodeIndex {
         DawgNodeIndex(0)
     }
 
-    pub fn walk_from_node(&self, start: DawgNodeIndex, letters: &str) -> Option<&DawgEdge> {
-        let mut maybe_node = start;
+    pub fn n_edges(&self) -> usize {
+        self.n_edges
+    }
+
+    /// The edge at `index`; a node's index is that of its first edge
+    pub fn edge(&self, index: DawgNodeIndex) -> DawgEdge {
+        let start = self.edges_start + index.0 as usize * EDGE_SIZE;
+        let bytes = self.data[start..start + EDGE_SIZE].try_into().unwrap();
+        DawgEdge::from(u32::from_le_bytes(bytes))
+    }
+
+    /// Every edge, in order
+    pub fn edges(&self) -> impl Iterator<Item = DawgEdge> + '_ {
+        (0..self.n_edges() as u32).map(move |index| self.edge(DawgNodeIndex(index)))
+    }
+
+    /// The packed edges followed by their letter masks, without the header
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data[self.edges_start..]
+    }
+
+    /// Copies borrowed edges so the dawg no longer borrows them
+    pub fn into_owned(self) -> Dawg<'static> {
+        Dawg {
+            alphabet: self.alphabet,
+            metadata: self.metadata,
+            data: Cow::Owned(self.data.into_owned()),
+            edges_start: self.edges_start,
+            n_edges: self.n_edges,
+        }
+    }
+
+    /// Follows the tiles of `letters` from `start`, returning the edge for the last letter if
+    /// every letter could be followed. Blanks may be given in uppercase.
+    pub fn walk_from_node(&self, start: DawgNodeIndex, letters: &str) -> Option<DawgEdge> {
+        let mut node = start;
         let mut maybe_edge = None;
         for ch in letters.chars() {
-            if maybe_node.is_some() {
-                maybe_edge = self.leaving_edge(maybe_node, ch);
-                if let Some(edge) = maybe_edge {
-                    maybe_node = edge.target;
-                    continue;
-                }
-            };
-            break;
+            if node.is_null() {
+                return None;
+            }
+            let edge = self.leaving_edge(node, self.alphabet.letter(ch)?)?;
+            node = edge.target;
+            maybe_edge = Some(edge);
         }
         maybe_edge
     }
 
-    pub fn walk_from_prior_edge<'a>(
-        &'a self,
-        prior_edge: &'a DawgEdge,
-        letters: &str,
-    ) -> Option<&'a DawgEdge> {
+    pub fn walk_from_prior_edge(&self, prior_edge: &DawgEdge, letters: &str) -> Option<DawgEdge> {
         if letters.is_empty() {
-            return Some(prior_edge);
+            return Some(*prior_edge);
         }
         if prior_edge.target.is_some() {
             self.walk_from_node(prior_edge.target, letters)
@@ -58,100 +327,452 @@ impl Dawg {
     }
 
     pub fn contains(&self, word: &str) -> bool {
-        self.walk_from_node(self.root(), word).is_some()
+        self.walk_from_node(self.root(), word)
+            .is_some_and(|edge| edge.word_terminator)
     }
 
-    pub fn leaving_edge(&self, node: DawgNodeIndex, ch: char) -> Option<&DawgEdge> {
-        for edge in &self[node..] {
-            if edge.letter == ch {
-                return Some(edge);
-            }
-            if edge.node_terminator {
-                break;
+    /// The letters of the edges leaving `node`, one bit per letter as in cross checks. The root
+    /// of an empty dawg has none.
+    pub fn child_letters(&self, node: DawgNodeIndex) -> u64 {
+        if node.0 as usize >= self.n_edges {
+            return 0;
+        }
+        let start = self.edges_start + self.n_edges * EDGE_SIZE + node.0 as usize * MASK_SIZE;
+        u64::from_le_bytes(self.data[start..start + MASK_SIZE].try_into().unwrap())
+    }
+
+    pub fn leaving_edge(&self, node: DawgNodeIndex, letter: Letter) -> Option<DawgEdge> {
+        let letters = self.child_letters(node);
+        if letter.index() >= MAX_LETTERS {
+            // A GADDAG's separator, which has no bit, comes after all the letters that do
+            let index = node.0 + letters.count_ones();
+            if index as usize >= self.n_edges()
+                || letters != 0 && self.edge(DawgNodeIndex(index - 1)).node_terminator
+            {
+                return None;
             }
+            let edge = self.edge(DawgNodeIndex(index));
+            return Some(edge).filter(|edge| edge.letter == letter);
+        }
+        let bit = 1 << letter.index();
+        if letters & bit == 0 {
+            return None;
         }
-        None
+        Some(self.edge(DawgNodeIndex(node.0 + (letters & (bit - 1)).count_ones())))
+    }
+
+    /// Writes a header with the alphabet and metadata, then the edges and their letter masks, in
+    /// the format read by `from_bytes`
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let header = DawgHeader {
+            alphabet: self.alphabet.clone(),
+            metadata: self.metadata.clone(),
+            n_edges: self.n_edges(),
+            checksum: checksum(self.as_bytes()),
+            len: 0,
+        };
+        header.write(writer)?;
+        writer.write_all(self.as_bytes())
     }
 
     pub fn apply_to_child_edges<F>(&self, node: DawgNodeIndex, mut f: F)
     where
         F: FnMut(&DawgEdge),
     {
-        for edge in self[node..].iter() {
-            f(edge);
+        let mut index = node.0;
+        while (index as usize) < self.n_edges() {
+            let edge = self.edge(DawgNodeIndex(index));
+            f(&edge);
             if edge.node_terminator {
                 break;
             }
+            index += 1;
+        }
+    }
+
+    /// Calls `f` on the edges leaving `node` whose letters are in `letters`, a bitmask as in
+    /// cross checks, without looking at the others
+    pub fn apply_to_child_edges_in<F>(&self, node: DawgNodeIndex, letters: u64, mut f: F)
+    where
+        F: FnMut(&DawgEdge),
+    {
+        let children = self.child_letters(node);
+        let mut wanted = children & letters;
+        while wanted != 0 {
+            let bit = wanted & wanted.wrapping_neg();
+            let index = node.0 + (children & (bit - 1)).count_ones();
+            f(&self.edge(DawgNodeIndex(index)));
+            wanted &= !bit;
         }
     }
 }
 
-impl Index<RangeFrom<DawgNodeIndex>> for Dawg {
-    type Output = [DawgEdge];
+impl Dawg<'static> {
+    /// Reads a dawg file written by `write_to`
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Dawg<'static>, DawgError> {
+        Dawg::from_vec(fs::read(path)?)
+    }
 
-    fn index(&self, index: RangeFrom<DawgNodeIndex>) -> &Self::Output {
-        &self.edges[index.start.0 as usize..]
+    /// Takes ownership of a dawg file written by `write_to`
+    pub fn from_vec(bytes: Vec<u8>) -> Result<Dawg<'static>, DawgError> {
+        Dawg::from_data(Cow::Owned(bytes), true)
+    }
+
+    /// Builds a dawg from words written as in `Alphabet::parse`, in any order
+    pub fn from_word_list<I, S>(alphabet: Alphabet, words: I) -> Result<Dawg<'static>, DawgError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut builder = DawgBuilder::new(alphabet);
+        builder.add_words(words)?;
+        Ok(builder.build())
     }
 }
 
-#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
+/// An edge as packed into 32 bits, much as in the paper: the letter index in the low 8 bits
+/// (5 in the paper), then the word and node terminator bits, then the target in the top 22 bits
+/// (16 in the paper)
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq)]
 pub struct DawgEdge {
-    // 5 bits in paper
-    pub letter: char,
-    //  1 bit in paper
+    pub letter: Letter,
     pub word_terminator: bool,
-    // 1 bit in paper
     pub node_terminator: bool,
-    // 16 bits in paper
     pub target: DawgNodeIndex,
 }
 
-pub const DAWG_EDGE_TO_ROOT: &'static DawgEdge = &DawgEdge {
-    letter: 'a',
+pub const DAWG_EDGE_TO_ROOT: DawgEdge = DawgEdge {
+    letter: Letter(0),
     word_terminator: false,
     node_terminator: false,
     target: DawgNodeIndex(0),
 };
 
-impl From<u64> for DawgEdge {
-    fn from(input: u64) -> Self {
-        const LETTER_OFFSET: u64 = 0;
-        const WORD_TERMINATOR_BIT_OFFSET: u64 = 8;
-        const NODE_TERMINATOR_BIT_OFFSET: u64 = 16;
-        const TARGET_BIT_OFFSET: u64 = 32;
+const EDGE_SIZE: usize = size_of::<u32>();
+const MASK_SIZE: usize = size_of::<u64>();
+
+/// The letters of each edge and the rest of its node, one bit per letter as in cross checks
+fn letter_masks(edges: &[DawgEdge]) -> Vec<u64> {
+    let mut masks = vec![0; edges.len()];
+    let mut rest_of_node = 0;
+    for (mask, edge) in masks.iter_mut().zip(edges).rev() {
+        if edge.node_terminator {
+            rest_of_node = 0;
+        }
+        // Only a GADDAG's separator can be past the last letter that fits
+        if edge.letter.index() < MAX_LETTERS {
+            rest_of_node |= 1 << edge.letter.index();
+        }
+        *mask = rest_of_node;
+    }
+    masks
+}
+
+const LETTER_MASK: u32 = 0xff;
+const WORD_TERMINATOR_BIT: u32 = 1 << 8;
+const NODE_TERMINATOR_BIT: u32 = 1 << 9;
+const TARGET_BIT_OFFSET: u32 = 10;
 
-        const MISSING_TARGET_FLAG: u32 = !0; // all ones
+/// Edges past this can't be targets, as this marks a missing target
+pub const MAX_DAWG_EDGES: u32 = (1 << (32 - TARGET_BIT_OFFSET)) - 1;
 
-        let letter = (input >> LETTER_OFFSET) as u8 as char;
-        let word_terminator = (input >> WORD_TERMINATOR_BIT_OFFSET) as u8 != 0;
-        let node_terminator = (input >> NODE_TERMINATOR_BIT_OFFSET) as u8 != 0;
-        let target = match (input >> TARGET_BIT_OFFSET) as u32 {
-            MISSING_TARGET_FLAG => DawgNodeIndex(NULL_DAWG_NODE_INDEX),
+impl From<u32> for DawgEdge {
+    fn from(input: u32) -> Self {
+        let target = match input >> TARGET_BIT_OFFSET {
+            MAX_DAWG_EDGES => DawgNodeIndex(NULL_DAWG_NODE_INDEX),
             other => DawgNodeIndex(other),
         };
-
         Self {
-            letter,
-            word_terminator,
-            node_terminator,
+            letter: Letter((input & LETTER_MASK) as u8),
+            word_terminator: input & WORD_TERMINATOR_BIT != 0,
+            node_terminator: input & NODE_TERMINATOR_BIT != 0,
             target,
         }
     }
 }
 
+impl From<&DawgEdge> for u32 {
+    fn from(edge: &DawgEdge) -> Self {
+        let target = match edge.target {
+            target if target.is_null() => MAX_DAWG_EDGES,
+            DawgNodeIndex(other) => other,
+        };
+        let mut packed = edge.letter.0 as u32 | (target << TARGET_BIT_OFFSET);
+        if edge.word_terminator {
+            packed |= WORD_TERMINATOR_BIT;
+        }
+        if edge.node_terminator {
+            packed |= NODE_TERMINATOR_BIT;
+        }
+        packed
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use crate::gaddag::Gaddag;
+    use crate::game::{Direction, Position, ScrabbleBoard, ScrabblePlay};
+    use crate::generate_plays;
+    use crate::header::DAWG_MAGIC;
     use crate::loading::load_dawg;
 
+    use super::*;
+
     #[test]
     fn test_load_dawg() {
         let dawg = load_dawg();
         assert!(dawg.contains("hello"));
         assert!(!dawg.contains("helloworld"));
+        assert!(!dawg.contains("hel"));
+        assert!(!dawg.contains("hellovas"));
+        assert!(!dawg.contains("helloe"));
 
         let mut root_children: Vec<char> = Vec::new();
-        dawg.apply_to_child_edges(dawg.root(), |edge| (&mut root_children).push(edge.letter));
+        dawg.apply_to_child_edges(dawg.root(), |edge| {
+            root_children.push(dawg.alphabet.symbol(edge.letter))
+        });
         let root_children: String = root_children.iter().collect();
         assert_eq!(root_children, "abcdefghijklmnopqrstuvwxyz")
     }
+
+    #[test]
+    fn test_from_bytes() {
+        let mut dawg = Dawg::from_word_list(Alphabet::english(), ["cat", "cats", "dog"]).unwrap();
+        dawg.metadata.lexicon = "Pets".to_string();
+        dawg.metadata.date = "2020-02-23".to_string();
+        let bytes = file_bytes(&dawg);
+        let read = Dawg::from_bytes(&bytes).unwrap();
+        assert_eq!(read.as_bytes(), dawg.as_bytes());
+        assert_eq!(read.alphabet, dawg.alphabet);
+        assert_eq!(read.metadata, dawg.metadata);
+        assert_eq!(read.metadata.word_count, 3);
+        assert!(read.contains("cats"));
+
+        let spanish = Dawg::from_word_list(Alphabet::spanish(), ["chorro"]).unwrap();
+        let read = Dawg::from_vec(file_bytes(&spanish)).unwrap();
+        assert_eq!(read.alphabet, Alphabet::spanish());
+        assert!(read.contains(&read.alphabet.parse("chorro").unwrap()));
+    }
+
+    #[test]
+    fn test_invalid_files() {
+        let dawg = Dawg::from_word_list(Alphabet::english(), ["cat", "cats", "dog"]).unwrap();
+        let bytes = file_bytes(&dawg);
+        assert!(matches!(
+            Dawg::from_bytes(b"cat\ncats\ndog\n"),
+            Err(DawgError::NotADawg)
+        ));
+        let mut future = bytes.clone();
+        future[DAWG_MAGIC.len()] = 3;
+        assert!(matches!(
+            Dawg::from_bytes(&future),
+            Err(DawgError::UnsupportedVersion(3))
+        ));
+        assert!(matches!(
+            Dawg::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(DawgError::Truncated)
+        ));
+        assert!(matches!(
+            Dawg::from_bytes(&bytes[..12]),
+            Err(DawgError::Truncated)
+        ));
+        let mut longer = bytes.clone();
+        longer.push(0);
+        assert!(matches!(
+            Dawg::from_bytes(&longer),
+            Err(DawgError::InvalidLength(1))
+        ));
+        let mut corrupted = bytes.clone();
+        *corrupted.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            Dawg::from_bytes(&corrupted),
+            Err(DawgError::ChecksumMismatch)
+        ));
+        assert!(matches!(
+            Dawg::from_path("no/such/dawg.bin"),
+            Err(DawgError::Io(_))
+        ));
+        assert!(matches!(
+            Dawg::from_word_list(Alphabet::english(), ["c4t"]),
+            Err(DawgError::InvalidWord(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_edges() {
+        let dawg = Dawg::from_word_list(Alphabet::english(), ["cat", "cats", "dog"]).unwrap();
+        let edges: Vec<DawgEdge> = dawg.edges().collect();
+        let with_edges = |edges: &[DawgEdge]| {
+            Dawg::from_vec(file_bytes(&Dawg::from_edges(Alphabet::english(), edges)))
+        };
+
+        let mut bad_letter = edges.clone();
+        bad_letter[1].letter = Letter(30);
+        assert!(matches!(
+            with_edges(&bad_letter),
+            Err(DawgError::InvalidLetter(1))
+        ));
+        let mut bad_target = edges.clone();
+        bad_target[0].target = DawgNodeIndex(1000);
+        assert!(matches!(
+            with_edges(&bad_target),
+            Err(DawgError::InvalidTarget(0))
+        ));
+        let mut unterminated = edges.clone();
+        unterminated.last_mut().unwrap().node_terminator = false;
+        assert!(matches!(
+            with_edges(&unterminated),
+            Err(DawgError::UnterminatedNode)
+        ));
+        let mut repeated = edges.clone();
+        repeated[1].letter = repeated[0].letter;
+        assert!(matches!(
+            with_edges(&repeated),
+            Err(DawgError::UnsortedNode(1))
+        ));
+        let mut swapped = edges.clone();
+        swapped.swap(0, 1);
+        swapped[0].node_terminator = false;
+        swapped[1].node_terminator = true;
+        assert!(matches!(
+            with_edges(&swapped),
+            Err(DawgError::UnsortedNode(1))
+        ));
+        let mut bad_mask = Dawg::from_edges(Alphabet::english(), &edges);
+        let masks_start = bad_mask.n_edges() * EDGE_SIZE;
+        bad_mask.data.to_mut()[masks_start] ^= 1;
+        assert!(matches!(
+            Dawg::from_vec(file_bytes(&bad_mask)),
+            Err(DawgError::InvalidLetterMask(0))
+        ));
+        let mut cyclic = edges.clone();
+        let last_target = cyclic
+            .iter()
+            .rposition(|edge| edge.target.is_some())
+            .unwrap();
+        cyclic[last_target].target = dawg.root();
+        assert!(matches!(
+            with_edges(&cyclic),
+            Err(DawgError::Cycle(index)) if index == last_target
+        ));
+        // A cycle the root can't reach still loops for walks starting inside it
+        let mut unreachable = edges.clone();
+        unreachable.push(DawgEdge {
+            letter: Letter(0),
+            word_terminator: true,
+            node_terminator: true,
+            target: DawgNodeIndex(edges.len() as u32),
+        });
+        assert!(matches!(
+            with_edges(&unreachable),
+            Err(DawgError::Cycle(index)) if index == edges.len()
+        ));
+    }
+
+    #[test]
+    fn test_empty_dawg() {
+        let empty = Dawg::from_word_list(Alphabet::english(), Vec::<&str>::new()).unwrap();
+        let loaded = Dawg::from_vec(file_bytes(&empty)).unwrap();
+        let mut board = ScrabbleBoard::default();
+        board.add_play(&ScrabblePlay {
+            start: Position { row: 7, col: 7 },
+            direction: Direction::Horizontal,
+            word: "hello".to_string(),
+        });
+        for dawg in [&empty, &loaded] {
+            assert!(!dawg.contains("a"));
+            assert!(!dawg.contains(""));
+            assert_eq!(dawg.child_letters(dawg.root()), 0);
+            assert!(dawg.hooks("at").front.is_empty());
+            assert_eq!(dawg.words().count(), 0);
+            assert!(generate_plays(dawg, "abcdefg", &board, 10).is_empty());
+            assert!(generate_plays(dawg, "abcdef_", &ScrabbleBoard::default(), 10).is_empty());
+            let gaddag = Gaddag::from_dawg(dawg);
+            assert!(gaddag
+                .leaving_edge(gaddag.root(), gaddag.separator())
+                .is_none());
+        }
+    }
+
+    #[test]
+    fn test_child_lookup() {
+        let dawg = load_dawg();
+        let nodes = iter::once(dawg.root()).chain(dawg.edges().take(2000).map(|e| e.target));
+        for node in nodes.filter(DawgNodeIndex::is_some) {
+            let mut children = Vec::new();
+            dawg.apply_to_child_edges(node, |edge| children.push(*edge));
+            for letter in dawg.alphabet.letters() {
+                let scanned = children.iter().find(|edge| edge.letter == letter).copied();
+                assert_eq!(dawg.leaving_edge(node, letter), scanned);
+            }
+        }
+
+        let letter_bit = |tile| 1 << dawg.alphabet.letter(tile).unwrap().index();
+        let wanted = letter_bit('a') | letter_bit('e') | letter_bit('z');
+        let mut found = String::new();
+        dawg.apply_to_child_edges_in(dawg.root(), wanted, |edge| {
+            found.push(dawg.alphabet.symbol(edge.letter))
+        });
+        assert_eq!(found, "aez");
+        // "cwm" and "cwtch"
+        let cw = dawg.walk_from_node(dawg.root(), "cw").unwrap();
+        assert_eq!(
+            dawg.child_letters(cw.target),
+            letter_bit('m') | letter_bit('t')
+        );
+    }
+
+    #[test]
+    fn test_separator_lookup() {
+        // With 64 letters, a GADDAG's separator has no bit in the node masks
+        let letters: String = ('a'..='z').chain('а'..='я').chain('α'..='ζ').collect();
+        let spellings: Vec<String> = letters.chars().map(String::from).collect();
+        let spellings: Vec<&str> = spellings.iter().map(String::as_str).collect();
+        let alphabet = Alphabet::new(&spellings).unwrap();
+        assert_eq!(alphabet.all_letters(), u64::MAX);
+        let gaddag = Gaddag::from_word_list(alphabet, ["ab", "ζa"]).unwrap();
+        let a = gaddag.alphabet().letter('a').unwrap();
+        let a_edge = gaddag.leaving_edge(gaddag.root(), a).unwrap();
+        let separator = gaddag
+            .leaving_edge(a_edge.target, gaddag.separator())
+            .unwrap();
+        let b = gaddag.alphabet().letter('b').unwrap();
+        assert!(
+            gaddag
+                .leaving_edge(separator.target, b)
+                .unwrap()
+                .word_terminator
+        );
+        let b_edge = gaddag.leaving_edge(gaddag.root(), b).unwrap();
+        assert!(gaddag.leaving_edge(b_edge.target, a).is_some());
+        assert!(gaddag
+            .leaving_edge(b_edge.target, gaddag.separator())
+            .is_none());
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(load_dawg().validate().is_ok());
+        let dawg = Dawg::from_word_list(Alphabet::spanish(), ["chorro", "carro", "ch"]).unwrap();
+        assert!(dawg.validate().is_ok());
+
+        // An empty dawg passes, and walking it finds nothing
+        let empty = Dawg::from_word_list(Alphabet::english(), Vec::<&str>::new()).unwrap();
+        assert!(empty.validate().is_ok());
+        let empty = Dawg::from_vec(file_bytes(&empty)).unwrap();
+        assert_eq!(empty.n_edges(), 0);
+        assert!(!empty.contains("a"));
+        let a = empty.alphabet.letter('a').unwrap();
+        assert!(empty.leaving_edge(empty.root(), a).is_none());
+        let mut n_children = 0;
+        empty.apply_to_child_edges(empty.root(), |_| n_children += 1);
+        empty.apply_to_child_edges_in(empty.root(), u64::MAX, |_| n_children += 1);
+        assert_eq!(n_children, 0);
+    }
+
+    fn file_bytes(dawg: &Dawg) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        dawg.write_to(&mut bytes).unwrap();
+        bytes
+    }
 }
@@ -0,0 +1,161 @@
+use crate::alphabet::{Alphabet, Letter};
+use crate::building::DawgBuilder;
+use crate::dawg::{Dawg, DawgEdge, DawgError, DawgNodeIndex};
+
+/// A GADDAG (Gordon, 1994): for every word, and every way of splitting it into a non-empty
+/// prefix and a suffix, the reversed prefix followed by a separator and the suffix. A whole word
+/// reversed needs no separator.
+///
+/// This lets a word be built outwards from any of its letters: first leftwards, then, after the
+/// separator, rightwards. It is stored as a minimised `Dawg` over the alphabet plus the separator,
+/// which is the letter after the alphabet's last. For the English lexicon it has about six times
+/// as many edges as the `Dawg` of the same words.
+#[derive(Debug)]
+pub struct Gaddag {
+    dawg: Dawg<'static>,
+}
+
+impl Gaddag {
+    /// Builds the GADDAG of the words in `dawg`
+    pub fn from_dawg(dawg: &Dawg) -> Gaddag {
+        let mut builder = DawgBuilder::new(dawg.alphabet.clone());
+        let separator = Letter(dawg.alphabet.len() as u8);
+        let mut word = Vec::new();
+        add_paths_below(dawg, dawg.root(), separator, &mut word, &mut builder);
+        Gaddag {
+            dawg: builder.build(),
+        }
+    }
+
+    /// Builds the GADDAG of words written as in `Alphabet::parse`
+    pub fn from_word_list<I, S>(alphabet: Alphabet, words: I) -> Result<Gaddag, DawgError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Ok(Gaddag::from_dawg(&Dawg::from_word_list(alphabet, words)?))
+    }
+
+    pub fn alphabet(&self) -> &Alphabet {
+        &self.dawg.alphabet
+    }
+
+    /// The letter marking the switch from the reversed prefix of a word to its suffix
+    pub fn separator(&self) -> Letter {
+        Letter(self.dawg.alphabet.len() as u8)
+    }
+
+    pub fn root(&self) -> DawgNodeIndex {
+        self.dawg.root()
+    }
+
+    pub fn leaving_edge(&self, node: DawgNodeIndex, letter: Letter) -> Option<DawgEdge> {
+        self.dawg.leaving_edge(node, letter)
+    }
+
+    /// Calls `f` on every edge leaving `node`, including the separator
+    pub fn apply_to_child_edges<F>(&self, node: DawgNodeIndex, f: F)
+    where
+        F: FnMut(&DawgEdge),
+    {
+        self.dawg.apply_to_child_edges(node, f)
+    }
+
+    /// Calls `f` on the edges leaving `node` whose letters are in the bitmask `letters`
+    pub fn apply_to_child_edges_in<F>(&self, node: DawgNodeIndex, letters: u64, f: F)
+    where
+        F: FnMut(&DawgEdge),
+    {
+        self.dawg.apply_to_child_edges_in(node, letters, f)
+    }
+
+    /// Whether the word with the given tiles is in the GADDAG, found through its reversal
+    pub fn contains(&self, word: &str) -> bool {
+        let mut node = self.root();
+        let mut is_word = false;
+        for tile in word.chars().rev() {
+            let edge = self
+                .dawg
+                .alphabet
+                .letter(tile)
+                .and_then(|letter| self.leaving_edge(node, letter));
+            match edge {
+                Some(edge) => {
+                    node = edge.target;
+                    is_word = edge.word_terminator;
+                }
+                None => return false,
+            }
+        }
+        is_word
+    }
+
+    pub fn n_edges(&self) -> usize {
+        self.dawg.n_edges()
+    }
+}
+
+/// Adds the paths of every word below `node` of `dawg`, whose first letters are in `word`
+fn add_paths_below(
+    dawg: &Dawg,
+    node: DawgNodeIndex,
+    separator: Letter,
+    word: &mut Vec<Letter>,
+    builder: &mut DawgBuilder,
+) {
+    if node.is_null() {
+        return;
+    }
+    dawg.apply_to_child_edges(node, |edge| {
+        word.push(edge.letter);
+        if edge.word_terminator {
+            add_paths(word, separator, builder);
+        }
+        add_paths_below(dawg, edge.target, separator, word, builder);
+        word.pop();
+    });
+}
+
+fn add_paths(word: &[Letter], separator: Letter, builder: &mut DawgBuilder) {
+    let mut path = Vec::with_capacity(word.len() + 1);
+    for split in 1..=word.len() {
+        path.clear();
+        path.extend(word[..split].iter().rev());
+        if split < word.len() {
+            path.push(separator);
+            path.extend(&word[split..]);
+        }
+        builder.add_letters(&path);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_paths() {
+        let gaddag = Gaddag::from_word_list(Alphabet::english(), ["care", "car", "bar"]).unwrap();
+        assert!(gaddag.contains("care") && gaddag.contains("car") && gaddag.contains("bar"));
+        assert!(!gaddag.contains("ca") && !gaddag.contains("bare"));
+
+        // "care" can be built outwards from its "r": "r", "a", "c", separator, "e"
+        let alphabet = gaddag.alphabet();
+        let mut node = gaddag.root();
+        let mut last_edge = None;
+        for letter in "rac".chars().map(|tile| alphabet.letter(tile).unwrap()) {
+            let edge = gaddag.leaving_edge(node, letter).unwrap();
+            node = edge.target;
+            last_edge = Some(edge);
+        }
+        assert!(last_edge.unwrap().word_terminator);
+        let separator = gaddag.leaving_edge(node, gaddag.separator()).unwrap();
+        let e = alphabet.letter('e').unwrap();
+        assert!(
+            gaddag
+                .leaving_edge(separator.target, e)
+                .unwrap()
+                .word_terminator
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use crate::game::rng::Rng;
+use crate::game::ruleset::TileSet;
+
+#[derive(Debug, Clone)]
+pub struct TileBag {
+    tiles: Vec<char>,
+    rng: Rng,
+}
+
+impl TileBag {
+    pub fn new(tiles: &str, seed: u64) -> TileBag {
+        TileBag {
+            tiles: tiles.chars().collect(),
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// A full bag of the tiles in `tile_set`
+    pub fn full(tile_set: &TileSet, seed: u64) -> TileBag {
+        TileBag::new(&tile_set.tiles(), seed)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    pub fn tiles(&self) -> &[char] {
+        &self.tiles
+    }
+
+    /// Takes a random tile out of the bag
+    pub fn draw(&mut self) -> Option<char> {
+        if self.tiles.is_empty() {
+            None
+        } else {
+            let index = self.rng.below(self.tiles.len());
+            Some(self.tiles.swap_remove(index))
+        }
+    }
+
+    pub fn return_tile(&mut self, tile: char) {
+        self.tiles.push(tile);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::util::BLANK_TILE_CHAR;
+
+    use super::*;
+
+    #[test]
+    fn test_full() {
+        let bag = TileBag::full(&TileSet::english(), 0);
+        assert_eq!(bag.len(), 100);
+        assert_eq!(bag.tiles().iter().filter(|&&tile| tile == 'e').count(), 12);
+        assert_eq!(
+            bag.tiles()
+                .iter()
+                .filter(|&&tile| tile == BLANK_TILE_CHAR)
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_draw_is_seeded() {
+        let mut first = TileBag::full(&TileSet::english(), 7);
+        let mut second = TileBag::full(&TileSet::english(), 7);
+        let first_draws: String = (0..100).filter_map(|_| first.draw()).collect();
+        let second_draws: String = (0..100).filter_map(|_| second.draw()).collect();
+        assert_eq!(first_draws, second_draws);
+        assert_eq!(first_draws.len(), 100);
+        assert!(first.draw().is_none());
+    }
+}
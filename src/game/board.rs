@@ -1,66 +1,88 @@
 use std::collections::VecDeque;
 use std::ops::{Index, IndexMut};
 
+use crate::alphabet::Alphabet;
 use crate::dawg::Dawg;
 use crate::game::checked_square::{CheckedAisleSquare, CheckedBoardSquare};
 use crate::game::cross_checks::CrossChecks;
+use crate::game::layout::BoardLayout;
 use crate::game::play_generation::ScrabblePlay;
-use crate::game::scoring::ScoreModifier;
+use crate::game::ruleset::TileSet;
+use crate::game::util::EMPTY_SQUARE_CHAR;
 use crate::game::util::{Direction, Position};
-use crate::game::util::{BLANK_TILE_CHAR, EMPTY_SQUARE_CHAR};
-
-pub const BOARD_SIZE: usize = 15;
 
 #[derive(Debug, Clone)]
 pub struct ScrabbleBoard {
-    pub squares: [[Option<char>; BOARD_SIZE]; BOARD_SIZE],
+    pub layout: BoardLayout,
+    /// The tiles on the board in row-major order
+    pub squares: Vec<Option<char>>,
 }
 
 impl ScrabbleBoard {
+    pub fn new(layout: BoardLayout) -> ScrabbleBoard {
+        let squares = vec![None; layout.n_squares()];
+        ScrabbleBoard { layout, squares }
+    }
+
+    #[allow(clippy::result_unit_err)]
     pub fn from_contents(contents: &str) -> Result<ScrabbleBoard, ()> {
-        let parsed_contents = ScrabbleBoard::parse_contents(contents)?;
+        ScrabbleBoard::from_layout_contents(BoardLayout::standard(), &Alphabet::english(), contents)
+    }
 
-        let mut board = ScrabbleBoard::default();
-        for (i, ch) in parsed_contents.iter().enumerate() {
-            let row = i / BOARD_SIZE;
-            let col = i % BOARD_SIZE;
-            *&mut board.squares[row][col] = *ch;
-        }
-        Ok(board)
+    /// Reads a board with one tile or space per square, in row-major order. Tiles are written as
+    /// in `Alphabet::parse`, so may take more than one character.
+    #[allow(clippy::result_unit_err)]
+    pub fn from_layout_contents(
+        layout: BoardLayout,
+        alphabet: &Alphabet,
+        contents: &str,
+    ) -> Result<ScrabbleBoard, ()> {
+        let squares = ScrabbleBoard::parse_contents(&layout, alphabet, contents)?;
+        Ok(ScrabbleBoard { layout, squares })
     }
 
-    fn parse_contents(contents: &str) -> Result<Vec<Option<char>>, ()> {
-        let tiles = ScrabbleBoard::parse_into_tiles(contents)?;
-        if tiles.len() != BOARD_SIZE * BOARD_SIZE {
+    fn parse_contents(
+        layout: &BoardLayout,
+        alphabet: &Alphabet,
+        contents: &str,
+    ) -> Result<Vec<Option<char>>, ()> {
+        let tiles = ScrabbleBoard::parse_into_tiles(alphabet, contents)?;
+        if tiles.len() != layout.n_squares() {
             Err(())
         } else {
             Ok(tiles)
         }
     }
 
-    fn parse_into_tiles(contents: &str) -> Result<Vec<Option<char>>, ()> {
-        let mut result: Vec<_> = Vec::with_capacity(BOARD_SIZE * BOARD_SIZE);
-        for ch in contents.chars() {
-            if ch == '\n' {
-                continue; // ignore newlines
-            }
-            let parsed_ch = match ch {
-                ch if ch.is_ascii_lowercase() => Some(ch),
-                BLANK_TILE_CHAR => Some(BLANK_TILE_CHAR),
-                EMPTY_SQUARE_CHAR => None,
+    fn parse_into_tiles(alphabet: &Alphabet, contents: &str) -> Result<Vec<Option<char>>, ()> {
+        let mut result: Vec<_> = Vec::with_capacity(contents.len());
+        let mut rest = contents;
+        while let Some(ch) = rest.chars().next() {
+            let (parsed_ch, n_bytes) = match ch {
+                '\n' => {
+                    rest = &rest[1..]; // ignore newlines
+                    continue;
+                }
+                EMPTY_SQUARE_CHAR => (None, 1),
                 _ => {
-                    return Err(());
+                    let (tile, n_bytes) = alphabet.next_tile(rest).ok_or(())?;
+                    (Some(tile), n_bytes)
                 }
             };
             result.push(parsed_ch);
+            rest = &rest[n_bytes..];
         }
         Ok(result)
     }
 
+    /// Places the tiles of `play` on the empty squares it covers; tiles already on the board
+    /// (including blanks) are left as they are.
     pub fn add_play(&mut self, play: &ScrabblePlay) {
         let mut position = play.start;
         for ch in play.word.chars() {
-            self[position] = Some(ch);
+            if self[position].is_none() {
+                self[position] = Some(ch);
+            }
             position = position.step(play.direction);
         }
     }
@@ -74,15 +96,16 @@ impl ScrabbleBoard {
     }
 
     fn render(&self, show_modifiers: bool, include_newlines: bool) -> String {
-        let mut result = String::with_capacity(BOARD_SIZE * (BOARD_SIZE + 1));
-        for (row, row_contents) in self.squares.iter().enumerate() {
-            for (col, square) in row_contents.iter().enumerate() {
+        let layout = &self.layout;
+        let mut result = String::with_capacity(layout.rows() * (layout.cols() + 1));
+        for row in 0..layout.rows() {
+            for col in 0..layout.cols() {
                 let position = Position { row, col };
-                let next_char = match &square {
-                    Some(ch) => *ch,
+                let next_char = match self[position] {
+                    Some(ch) => ch,
                     None => {
                         if show_modifiers {
-                            ScoreModifier::at(position).as_char()
+                            layout.modifier(position).as_char()
                         } else {
                             EMPTY_SQUARE_CHAR
                         }
@@ -97,11 +120,11 @@ impl ScrabbleBoard {
         result
     }
 
-    pub fn to_checked_board(&self, dawg: &Dawg) -> CheckedScrabbleBoard {
-        let mut checked_board = CheckedScrabbleBoard::default();
+    pub fn to_checked_board(&self, dawg: &Dawg, tile_set: &TileSet) -> CheckedScrabbleBoard {
+        let mut checked_board = CheckedScrabbleBoard::new(self.layout.clone());
         for &direction in Direction::iterator() {
-            for row in 0..BOARD_SIZE {
-                for col in 0..BOARD_SIZE {
+            for row in 0..self.layout.rows() {
+                for col in 0..self.layout.cols() {
                     let position = Position { row, col };
                     let square = &mut checked_board[position];
                     let tile = self[position];
@@ -114,16 +137,19 @@ impl ScrabbleBoard {
                             let preceding = CrossChecks::unwrap_or_empty(preceding.as_ref());
                             let following = CrossChecks::unwrap_or_empty(following.as_ref());
                             *square.checks_mut(direction) =
-                                Some(CrossChecks::create(dawg, preceding, following));
+                                Some(CrossChecks::create(dawg, tile_set, preceding, following));
                         }
                     }
                 }
             }
         }
+        if self.is_empty() {
+            checked_board[self.layout.start()].is_start = true;
+        }
         checked_board
     }
 
-    fn preceding(&self, position: Position, direction: Direction) -> Option<String> {
+    pub fn preceding(&self, position: Position, direction: Direction) -> Option<String> {
         let mut position = position;
         let mut result: VecDeque<char> = VecDeque::new();
         let cross_idx = position.cross(direction);
@@ -144,12 +170,13 @@ impl ScrabbleBoard {
         }
     }
 
-    fn following(&self, position: Position, direction: Direction) -> Option<String> {
+    pub fn following(&self, position: Position, direction: Direction) -> Option<String> {
         let mut position = position;
         let mut result = String::new();
         let cross_idx = position.cross(direction);
-        if cross_idx < BOARD_SIZE {
-            for following_cross_idx in cross_idx + 1..BOARD_SIZE {
+        let aisle_len = self.layout.aisle_len(direction);
+        if cross_idx < aisle_len {
+            for following_cross_idx in cross_idx + 1..aisle_len {
                 position.set_cross(direction, following_cross_idx);
                 if let Some(tile) = self[position] {
                     result.push(tile);
@@ -164,63 +191,96 @@ impl ScrabbleBoard {
             Some(result)
         }
     }
+
+    /// The word formed perpendicular to `direction` by placing `ch` at `position`, if any
+    pub fn cross_word(
+        &self,
+        position: Position,
+        direction: Direction,
+        ch: char,
+    ) -> Option<ScrabblePlay> {
+        let cross_direction = direction.perpendicular();
+        let preceding = self.preceding(position, cross_direction);
+        let following = self.following(position, cross_direction);
+        if preceding.is_none() && following.is_none() {
+            return None;
+        }
+        let preceding = preceding.unwrap_or_default();
+        let following = following.unwrap_or_default();
+        let start = Position::from_aisle_cross(
+            cross_direction,
+            position.aisle(cross_direction),
+            position.cross(cross_direction) - preceding.chars().count(),
+        );
+        Some(ScrabblePlay {
+            start,
+            direction: cross_direction,
+            word: format!("{}{}{}", preceding, ch, following),
+        })
+    }
 }
 
 impl Index<Position> for ScrabbleBoard {
     type Output = Option<char>;
     fn index(&self, position: Position) -> &Self::Output {
-        &self.squares[position.row][position.col]
+        &self.squares[self.layout.square_index(position)]
     }
 }
 
 impl IndexMut<Position> for ScrabbleBoard {
     fn index_mut(&mut self, position: Position) -> &mut Self::Output {
-        &mut self.squares[position.row][position.col]
+        let index = self.layout.square_index(position);
+        &mut self.squares[index]
     }
 }
 
 impl Default for ScrabbleBoard {
     fn default() -> ScrabbleBoard {
-        ScrabbleBoard {
-            squares: [[None; BOARD_SIZE]; BOARD_SIZE],
-        }
+        ScrabbleBoard::new(BoardLayout::standard())
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct CheckedScrabbleBoard {
-    pub squares: [[CheckedBoardSquare; BOARD_SIZE]; BOARD_SIZE],
+    pub layout: BoardLayout,
+    /// The checked squares in row-major order
+    pub squares: Vec<CheckedBoardSquare>,
 }
 
 impl CheckedScrabbleBoard {
-    pub fn aisle(&self, direction: Direction, index: usize) -> [CheckedAisleSquare; BOARD_SIZE] {
-        let mut aisle_contents: [CheckedAisleSquare; BOARD_SIZE] = Default::default();
-        for cross in 0..BOARD_SIZE {
-            let position = Position::from_aisle_cross(direction, index, cross);
-            let square = &self[position];
-            aisle_contents[cross] = square.to_checked_row_square(direction);
-        }
-        aisle_contents
+    pub fn new(layout: BoardLayout) -> CheckedScrabbleBoard {
+        let squares = vec![CheckedBoardSquare::default(); layout.n_squares()];
+        CheckedScrabbleBoard { layout, squares }
+    }
+
+    pub fn aisle(&self, direction: Direction, index: usize) -> Vec<CheckedAisleSquare> {
+        (0..self.layout.aisle_len(direction))
+            .map(|cross| {
+                let position = Position::from_aisle_cross(direction, index, cross);
+                let mut aisle_square = self[position].to_checked_row_square(direction);
+                aisle_square.score_modifier = self.layout.modifier(position);
+                aisle_square
+            })
+            .collect()
+    }
+
+    /// Whether this is the board before the opening play
+    pub fn is_empty(&self) -> bool {
+        self.squares.iter().all(|square| square.tile.is_none())
     }
 }
 
 impl Index<Position> for CheckedScrabbleBoard {
     type Output = CheckedBoardSquare;
     fn index(&self, position: Position) -> &Self::Output {
-        &self.squares[position.row][position.col]
+        &self.squares[self.layout.square_index(position)]
     }
 }
 
 impl IndexMut<Position> for CheckedScrabbleBoard {
     fn index_mut(&mut self, position: Position) -> &mut Self::Output {
-        &mut self.squares[position.row][position.col]
-    }
-}
-
-impl Default for CheckedScrabbleBoard {
-    fn default() -> CheckedScrabbleBoard {
-        let squares: [[CheckedBoardSquare; BOARD_SIZE]; BOARD_SIZE] = Default::default();
-        CheckedScrabbleBoard { squares }
+        let index = self.layout.square_index(position);
+        &mut self.squares[index]
     }
 }
 
@@ -231,21 +291,21 @@ mod test {
     #[test]
     fn test_default_display() {
         let expected = "\
-6  2       2  6
+6  2   6   2  6
  4   3   3   4 
   4   2 2   4  
 2  4   2   4  2
     4     4    
  3   3   3   3 
   2   2 2   2  
-   2   4   2   
+6  2   4   2  6
   2   2 2   2  
  3   3   3   3 
     4     4    
 2  4   2   4  2
   4   2 2   4  
  4   3   3   4 
-6  2       2  6
+6  2   6   2  6
 ";
         let actual = ScrabbleBoard::default().display();
         assert_eq!(expected, actual)
@@ -254,21 +314,21 @@ mod test {
     #[test]
     fn test_add_word() {
         let expected = "\
-6  2       2  6
+6  2   6   2  6
  4   3   3   4 
   4   2 2   4  
 2  4   2   4  2
     4     4    
  3   3   3   3 
   2   2 2   2  
-   2   hello   
+6  2   hello  6
   2   2 2   2  
  3   3   3   3 
     4     4    
 2  4   2   4  2
   4   2 2   4  
  4   3   3   4 
-6  2       2  6
+6  2   6   2  6
 ";
         let mut board = ScrabbleBoard::default();
         let play = ScrabblePlay {
@@ -281,6 +341,36 @@ mod test {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_add_word_with_blank() {
+        let mut board = ScrabbleBoard::default();
+        let play = ScrabblePlay {
+            start: Position { row: 7, col: 7 },
+            direction: Direction::Horizontal,
+            word: "heLlo".to_string(),
+        };
+        board.add_play(&play);
+        assert_eq!(board[Position { row: 7, col: 9 }], Some('L'));
+        assert_eq!(
+            board.preceding(Position { row: 7, col: 12 }, Direction::Horizontal),
+            Some("heLlo".to_string())
+        );
+    }
+
+    #[test]
+    fn test_multi_char_tiles() {
+        let alphabet = Alphabet::spanish();
+        let layout = BoardLayout::from_text("...\n...\n...\n").unwrap();
+        let board =
+            ScrabbleBoard::from_layout_contents(layout, &alphabet, "   \nchLLo\n   ").unwrap();
+        let middle = board.preceding(Position { row: 1, col: 3 }, Direction::Horizontal);
+        assert_eq!(alphabet.spell(&middle.unwrap()), "chLLo");
+
+        let layout = BoardLayout::from_text("..\n").unwrap();
+        assert!(ScrabbleBoard::from_layout_contents(layout.clone(), &alphabet, "k ").is_err());
+        assert!(ScrabbleBoard::from_layout_contents(layout, &alphabet, "rrr").is_ok());
+    }
+
     #[test]
     fn test_preceding_horizontal() {
         let mut board = ScrabbleBoard::default();
@@ -1,13 +1,17 @@
+use crate::alphabet::Letter;
 use crate::game::cross_checks::CrossChecks;
+use crate::game::scoring::ScoreModifier;
 use crate::game::util::Direction;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct CheckedBoardSquare {
     pub tile: Option<char>,
     /// The checks determined by horizontal neighbors (for use while solving a vertical row ):
     pub horizontal_cross_checks: Option<CrossChecks>,
     /// The checks determined by vertical neighbors (for use while solving a horizontal row):
     pub vertical_cross_checks: Option<CrossChecks>,
+    /// The start square of an empty board, which the opening play has to cover
+    pub is_start: bool,
 }
 
 impl CheckedBoardSquare {
@@ -16,12 +20,14 @@ impl CheckedBoardSquare {
             Direction::Horizontal => self.vertical_cross_checks.clone(),
             Direction::Vertical => self.horizontal_cross_checks.clone(),
         };
-        let is_anchor =
-            self.horizontal_cross_checks.is_some() || self.vertical_cross_checks.is_some();
+        let is_anchor = self.is_start
+            || self.horizontal_cross_checks.is_some()
+            || self.vertical_cross_checks.is_some();
         CheckedAisleSquare {
             tile: self.tile,
             cross_checks,
             is_anchor,
+            score_modifier: ScoreModifier::Plain,
         }
     }
 
@@ -33,39 +39,29 @@ impl CheckedBoardSquare {
     }
 }
 
-impl Default for CheckedBoardSquare {
-    fn default() -> CheckedBoardSquare {
-        CheckedBoardSquare {
-            tile: None,
-            horizontal_cross_checks: None,
-            vertical_cross_checks: None,
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct CheckedAisleSquare {
     pub tile: Option<char>,
     pub cross_checks: Option<CrossChecks>,
     /// Need to explicitly track whether a square is an anchor since we only have one of the cross checks
     pub is_anchor: bool,
+    /// The premium printed on the square, which only applies if the tile is placed by the play
+    pub score_modifier: ScoreModifier,
 }
 
 impl CheckedAisleSquare {
-    pub fn is_compatible(&self, letter: char) -> bool {
+    pub fn is_compatible(&self, letter: Letter) -> bool {
         self.cross_checks
             .as_ref()
-            .and_then(|checks| Some(checks.allows(letter)))
+            .map(|checks| checks.allows(letter))
             .unwrap_or(true)
     }
-}
 
-impl Default for CheckedAisleSquare {
-    fn default() -> CheckedAisleSquare {
-        CheckedAisleSquare {
-            tile: None,
-            cross_checks: None,
-            is_anchor: false,
-        }
+    /// The letters that can be placed here, as a bitmask by letter index
+    pub fn compatible_letters(&self) -> u64 {
+        self.cross_checks
+            .as_ref()
+            .map(CrossChecks::allowed_letters)
+            .unwrap_or(u64::MAX)
     }
 }
@@ -1,26 +1,29 @@
 use std::fmt;
 
+use crate::alphabet::{Alphabet, Letter, MAX_LETTERS};
 use crate::dawg::{Dawg, DAWG_EDGE_TO_ROOT};
-use crate::game::scoring::letter_value;
-use crate::loading::A_INDEX;
+use crate::game::ruleset::TileSet;
 
 #[derive(Clone)]
 pub struct CrossChecks {
-    /// allowed is a bitmask marking which letters are valid for the square
-    allowed: u32,
+    /// allowed is a bitmask marking which letters are valid for the square, by letter index
+    allowed: u64,
     /// cross_sum should hold the total points associated with neighboring letters for the sake of score computation
     pub cross_sum: i32,
 }
 
 impl CrossChecks {
-    pub fn set_allowed(&mut self, ch: char) {
-        let offset = (ch as u32) - ('a' as u32);
-        self.allowed |= 1 << offset;
+    pub fn set_allowed(&mut self, letter: Letter) {
+        self.allowed |= 1 << letter.0;
     }
 
-    pub fn allows(&self, ch: char) -> bool {
-        let offset = (ch as u32) - ('a' as u32);
-        ((1 << offset) & self.allowed) > 0
+    pub fn allows(&self, letter: Letter) -> bool {
+        ((1 << letter.0) & self.allowed) > 0
+    }
+
+    /// The allowed letters as a bitmask by letter index
+    pub fn allowed_letters(&self) -> u64 {
+        self.allowed
     }
 
     pub fn unwrap_or_empty(string: Option<&String>) -> &str {
@@ -31,7 +34,21 @@ impl CrossChecks {
         }
     }
 
-    pub fn create(dawg: &Dawg, preceding: &str, following: &str) -> CrossChecks {
+    /// `preceding` and `following` are board tiles, so may contain blanks as uppercase letters
+    pub fn create(
+        dawg: &Dawg,
+        tile_set: &TileSet,
+        preceding: &str,
+        following: &str,
+    ) -> CrossChecks {
+        let mut checks = CrossChecks::between(dawg, preceding, following);
+        checks.cross_sum = CrossChecks::cross_sum(tile_set, preceding, following);
+        checks
+    }
+
+    /// The letters that form a word of `dawg` when placed between `preceding` and `following`,
+    /// without a cross sum
+    pub fn between(dawg: &Dawg, preceding: &str, following: &str) -> CrossChecks {
         let mut checks = CrossChecks::default();
         let maybe_prior_edge = match preceding.is_empty() {
             true => Some(DAWG_EDGE_TO_ROOT),
@@ -47,28 +64,30 @@ impl CrossChecks {
                         }
                     }
                 });
-                checks.cross_sum = CrossChecks::cross_sum(preceding, following);
             }
         }
         checks
     }
 
-    fn cross_sum(preceding: &str, following: &str) -> i32 {
+    fn cross_sum(tile_set: &TileSet, preceding: &str, following: &str) -> i32 {
         preceding
             .chars()
             .chain(following.chars())
-            .map(|ch| letter_value(ch))
+            .map(|ch| tile_set.value(ch))
             .sum()
     }
 
-    fn letters(&self) -> String {
-        let mut letters = String::new();
-        for char_index in 0..27 {
-            if self.allowed & (1 << char_index) != 0 {
-                letters.push(char::from(A_INDEX + char_index as u8));
-            }
-        }
-        letters
+    fn letters(&self) -> impl Iterator<Item = Letter> + '_ {
+        (0..MAX_LETTERS as u8)
+            .map(Letter)
+            .filter(move |&letter| self.allows(letter))
+    }
+
+    /// The allowed letters, as they are stored on the board
+    pub fn symbols(&self, alphabet: &Alphabet) -> String {
+        self.letters()
+            .map(|letter| alphabet.symbol(letter))
+            .collect()
     }
 
     fn default() -> CrossChecks {
@@ -81,13 +100,15 @@ impl CrossChecks {
 
 impl fmt::Debug for CrossChecks {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "CrossChecks(letters=\"{}\")", self.letters())
+        let letters: Vec<u8> = self.letters().map(|letter| letter.0).collect();
+        write!(f, "CrossChecks(letters={:?})", letters)
     }
 }
 
 impl fmt::Display for CrossChecks {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "CrossChecks(letters=\"{}\")", self.letters())
+        let letters: Vec<u8> = self.letters().map(|letter| letter.0).collect();
+        write!(f, "CrossChecks(letters={:?})", letters)
     }
 }
 
@@ -100,35 +121,67 @@ mod test {
     #[test]
     fn test_cross_checks() {
         let mut checks = CrossChecks::default();
-        for c in (b'a'..b'z').map(char::from) {
-            assert!(!checks.allows(c));
-            checks.set_allowed(c);
-            assert!(checks.allows(c));
+        for letter in (0..MAX_LETTERS as u8).map(Letter) {
+            assert!(!checks.allows(letter));
+            checks.set_allowed(letter);
+            assert!(checks.allows(letter));
         }
     }
 
     #[test]
     fn test_display() {
         let mut checks = CrossChecks::default();
-        checks.set_allowed('a');
-        checks.set_allowed('b');
-        checks.set_allowed('z');
-        assert_eq!(checks.letters(), "abz");
+        checks.set_allowed(Letter(0));
+        checks.set_allowed(Letter(1));
+        checks.set_allowed(Letter(25));
+        assert_eq!(checks.symbols(&Alphabet::english()), "abz");
+        assert_eq!(checks.to_string(), "CrossChecks(letters=[0, 1, 25])");
     }
 
     #[test]
     fn test_create() {
         let dawg = load_dawg();
-        assert_eq!(CrossChecks::create(&dawg, "he", "lo").letters(), "l");
-        assert_eq!(CrossChecks::create(&dawg, "he", "oo").letters(), "");
-        assert_eq!(CrossChecks::create(&dawg, "hl", "lo").letters(), "");
+        let english = TileSet::english();
+        assert_eq!(
+            CrossChecks::create(dawg, &english, "he", "lo").symbols(&dawg.alphabet),
+            "l"
+        );
+        assert_eq!(
+            CrossChecks::create(dawg, &english, "he", "oo").symbols(&dawg.alphabet),
+            ""
+        );
+        assert_eq!(
+            CrossChecks::create(dawg, &english, "hl", "lo").symbols(&dawg.alphabet),
+            ""
+        );
     }
 
     #[test]
     fn test_create_2() {
         let dawg = load_dawg();
-        assert_eq!(CrossChecks::create(&dawg, "ru", "ty").letters(), "nst");
-        assert_eq!(CrossChecks::create(&dawg, "ru", "").letters(), "bcdegmnt");
-        assert_eq!(CrossChecks::create(&dawg, "", "ty").letters(), "s");
+        let english = TileSet::english();
+        assert_eq!(
+            CrossChecks::create(dawg, &english, "ru", "ty").symbols(&dawg.alphabet),
+            "nst"
+        );
+        assert_eq!(
+            CrossChecks::create(dawg, &english, "ru", "").symbols(&dawg.alphabet),
+            "bcdegmnt"
+        );
+        assert_eq!(
+            CrossChecks::create(dawg, &english, "", "ty").symbols(&dawg.alphabet),
+            "s"
+        );
+    }
+
+    #[test]
+    fn test_create_with_blanks() {
+        let dawg = load_dawg();
+        let english = TileSet::english();
+        assert_eq!(
+            CrossChecks::create(dawg, &english, "rU", "tY").symbols(&dawg.alphabet),
+            "nst"
+        );
+        assert_eq!(CrossChecks::create(dawg, &english, "rU", "tY").cross_sum, 2);
     }
 }
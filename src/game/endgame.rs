@@ -0,0 +1,462 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use crate::dawg::Dawg;
+use crate::game::board::ScrabbleBoard;
+use crate::game::play_generation::{PlayGenerator, ScoredMove, ScrabblePlay};
+use crate::game::rack::ScrabbleRack;
+use crate::game::ruleset::Ruleset;
+use crate::game::state::Move;
+use crate::game::util::{rack_tile, Position};
+
+/// More than any spread a game can end with
+const INFINITY: i32 = 1_000_000;
+
+/// Finds the best play-out of a position with an empty bag, where both racks are known, by an
+/// alpha-beta search over the plays of `PlayGenerator` deepened a turn at a time.
+///
+/// The game ends when a player goes out, which gains them twice the value of the tiles left on
+/// their opponent's rack, or when both players pass in a row, which costs each the value of
+/// their own rack. Exchanges aren't possible with an empty bag.
+pub struct EndgameSolver<'a> {
+    pub dawg: &'a Dawg<'a>,
+    pub ruleset: &'a Ruleset,
+    /// The most turns searched ahead. Positions still undecided there are valued as if neither
+    /// player could play again.
+    pub max_depth: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndgameSolution {
+    /// How far the player to move gains on their opponent over the rest of the game, including
+    /// the end-of-game rack adjustments
+    pub spread: i32,
+    /// The best moves of both players in turn, starting with the player to move
+    pub principal_variation: Vec<ScoredMove>,
+    /// The depth of the last search
+    pub depth: usize,
+    /// Whether the search saw the end of the game along every line, rather than stopping at
+    /// `max_depth`
+    pub is_exact: bool,
+}
+
+impl<'a> EndgameSolver<'a> {
+    /// Solves the endgame on `board` with `rack` to move against `opponent`
+    pub fn solve(
+        &self,
+        board: &ScrabbleBoard,
+        rack: &ScrabbleRack,
+        opponent: &ScrabbleRack,
+    ) -> EndgameSolution {
+        let mut search = Search {
+            solver: self,
+            board: board.clone(),
+            racks: [rack.clone(), opponent.clone()],
+            table: HashMap::new(),
+        };
+        let mut solution = None;
+        for depth in 1..=self.max_depth.max(1) {
+            let line = search.negamax(depth, -INFINITY, INFINITY, false);
+            solution = Some(EndgameSolution {
+                spread: line.value,
+                principal_variation: line.moves,
+                depth,
+                is_exact: line.is_complete,
+            });
+            if line.is_complete {
+                break;
+            }
+        }
+        solution.unwrap()
+    }
+}
+
+/// The best line found from a position, valued for the player to move
+struct Line {
+    value: i32,
+    moves: Vec<ScoredMove>,
+    /// Whether every line that decided the value reached the end of the game
+    is_complete: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    Exact,
+    /// The value is at least this, as the search was cut off by a better reply elsewhere
+    Lower,
+    /// The value is at most this, as no move beat what the opponent already had elsewhere
+    Upper,
+}
+
+struct TableEntry {
+    /// How far ahead the value was searched, or `usize::MAX` if it reached the end of the game
+    depth: usize,
+    value: i32,
+    bound: Bound,
+    /// The principal variation for exact values, otherwise just the best move found
+    moves: Vec<ScoredMove>,
+}
+
+struct Search<'s, 'a> {
+    solver: &'s EndgameSolver<'a>,
+    board: ScrabbleBoard,
+    /// The rack of the player to move, then their opponent's
+    racks: [ScrabbleRack; 2],
+    /// Transpositions of positions reached through other orders of the same moves
+    table: HashMap<u64, TableEntry>,
+}
+
+impl<'s, 'a> Search<'s, 'a> {
+    /// `passed` is whether the opponent passed on the turn before
+    fn negamax(&mut self, depth: usize, mut alpha: i32, mut beta: i32, passed: bool) -> Line {
+        if depth == 0 {
+            return Line {
+                value: self.rack_value(1) - self.rack_value(0),
+                moves: Vec::new(),
+                is_complete: false,
+            };
+        }
+        let key = self.key(passed);
+        let mut hint = None;
+        if let Some(entry) = self.table.get(&key) {
+            if entry.depth >= depth {
+                match entry.bound {
+                    Bound::Exact => alpha = beta,
+                    Bound::Lower => alpha = alpha.max(entry.value),
+                    Bound::Upper => beta = beta.min(entry.value),
+                }
+                if alpha >= beta {
+                    return Line {
+                        value: entry.value,
+                        moves: entry.moves.clone(),
+                        is_complete: entry.depth == usize::MAX,
+                    };
+                }
+            }
+            hint = entry.moves.first().cloned();
+        }
+
+        let original_alpha = alpha;
+        let mut best: Option<Line> = None;
+        let mut is_complete = true;
+        for scored in self.ordered_moves(hint) {
+            let line = self.after(&scored, depth, alpha, beta, passed);
+            is_complete &= line.is_complete;
+            if best.as_ref().is_none_or(|best| line.value > best.value) {
+                best = Some(line);
+            }
+            alpha = alpha.max(best.as_ref().unwrap().value);
+            if alpha >= beta {
+                break;
+            }
+        }
+        // There is always at least a pass
+        let mut best = best.unwrap();
+        best.is_complete = is_complete;
+
+        let bound = if best.value <= original_alpha {
+            Bound::Upper
+        } else if best.value >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        let moves = match bound {
+            Bound::Exact => best.moves.clone(),
+            _ => best.moves.iter().take(1).cloned().collect(),
+        };
+        self.table.insert(
+            key,
+            TableEntry {
+                depth: if is_complete { usize::MAX } else { depth },
+                value: best.value,
+                bound,
+                moves,
+            },
+        );
+        best
+    }
+
+    /// Makes `scored` for the player to move, searches the reply and unmakes it, returning the
+    /// line starting with it
+    fn after(
+        &mut self,
+        scored: &ScoredMove,
+        depth: usize,
+        alpha: i32,
+        beta: i32,
+        passed: bool,
+    ) -> Line {
+        let mut line = match &scored.played {
+            Move::Place(play) => {
+                let made = self.make(play);
+                let line = if self.racks[0].is_empty() {
+                    Line {
+                        value: scored.score + 2 * self.rack_value(1),
+                        moves: Vec::new(),
+                        is_complete: true,
+                    }
+                } else {
+                    // The reply's value is offset by the score, and so is the window
+                    let reply = self.reply(depth, alpha - scored.score, beta - scored.score, false);
+                    Line {
+                        value: scored.score - reply.value,
+                        ..reply
+                    }
+                };
+                self.unmake(&made);
+                line
+            }
+            _ if passed => Line {
+                value: self.rack_value(1) - self.rack_value(0),
+                moves: Vec::new(),
+                is_complete: true,
+            },
+            _ => {
+                let reply = self.reply(depth, alpha, beta, true);
+                Line {
+                    value: -reply.value,
+                    ..reply
+                }
+            }
+        };
+        line.moves.insert(0, scored.clone());
+        line
+    }
+
+    /// Searches the opponent's best line, from their point of view
+    fn reply(&mut self, depth: usize, alpha: i32, beta: i32, passed: bool) -> Line {
+        self.racks.swap(0, 1);
+        let reply = self.negamax(depth - 1, -beta, -alpha, passed);
+        self.racks.swap(0, 1);
+        reply
+    }
+
+    /// The plays of the player to move, those that go out first and then by score, then a pass.
+    /// `hint`, the best move found by an earlier search, comes before them all.
+    fn ordered_moves(&self, hint: Option<ScoredMove>) -> Vec<ScoredMove> {
+        let solver = self.solver;
+        let generator = PlayGenerator {
+            dawg: solver.dawg,
+            ruleset: solver.ruleset,
+            checked_board: self
+                .board
+                .to_checked_board(solver.dawg, &solver.ruleset.tile_set),
+            rack: self.racks[0].clone(),
+        };
+        let mut plays = generator.plays();
+        let rack_len = self.racks[0].len();
+        plays.sort_by_cached_key(|scored| {
+            let goes_out = scored.play.placed_tiles(&self.board).chars().count() == rack_len;
+            (!goes_out, -scored.score)
+        });
+        let mut moves: Vec<ScoredMove> = plays.into_iter().map(ScoredMove::from).collect();
+        moves.push(ScoredMove {
+            played: Move::Pass,
+            score: 0,
+        });
+        if let Some(hint) = hint {
+            if let Some(index) = moves.iter().position(|scored| *scored == hint) {
+                let hint = moves.remove(index);
+                moves.insert(0, hint);
+            }
+        }
+        moves
+    }
+
+    /// Places `play` from the rack of the player to move, returning the squares it filled
+    fn make(&mut self, play: &ScrabblePlay) -> Vec<Position> {
+        let mut made = Vec::new();
+        for (position, tile) in play.positions().zip(play.word.chars()) {
+            if self.board[position].is_none() {
+                self.board[position] = Some(tile);
+                self.racks[0].remove_tile(rack_tile(tile));
+                made.push(position);
+            }
+        }
+        made
+    }
+
+    fn unmake(&mut self, made: &[Position]) {
+        for &position in made {
+            let tile = self.board[position].take().unwrap();
+            self.racks[0].add_tile(rack_tile(tile));
+        }
+    }
+
+    fn rack_value(&self, index: usize) -> i32 {
+        self.racks[index].value(&self.solver.ruleset.tile_set)
+    }
+
+    fn key(&self, passed: bool) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.board.contents().hash(&mut hasher);
+        self.racks[0].tiles().hash(&mut hasher);
+        self.racks[1].tiles().hash(&mut hasher);
+        passed.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::util::Direction;
+    use crate::loading::load_dawg;
+
+    fn hello_board() -> ScrabbleBoard {
+        let mut board = ScrabbleBoard::default();
+        board.add_play(&ScrabblePlay {
+            start: Position { row: 7, col: 7 },
+            direction: Direction::Horizontal,
+            word: "hello".to_string(),
+        });
+        board
+    }
+
+    fn solve(rack: &str, opponent: &str) -> EndgameSolution {
+        let ruleset = Ruleset::english();
+        let solver = EndgameSolver {
+            dawg: load_dawg(),
+            ruleset: &ruleset,
+            max_depth: 6,
+        };
+        solver.solve(
+            &hello_board(),
+            &ScrabbleRack::new(rack),
+            &ScrabbleRack::new(opponent),
+        )
+    }
+
+    /// The spread of a line ending the game, for the player making its first move
+    fn line_spread(moves: &[ScoredMove]) -> i32 {
+        moves
+            .iter()
+            .enumerate()
+            .map(|(turn, scored)| {
+                if turn % 2 == 0 {
+                    scored.score
+                } else {
+                    -scored.score
+                }
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_going_out() {
+        let solution = solve("s", "qz");
+        assert!(solution.is_exact);
+        assert_eq!(solution.principal_variation.len(), 1);
+        let out = &solution.principal_variation[0];
+        assert!(matches!(out.played, Move::Place(_)));
+        assert_eq!(solution.spread, out.score + 2 * 20);
+    }
+
+    #[test]
+    fn test_stuck_with_q() {
+        let solution = solve("q", "at");
+        assert!(solution.is_exact);
+        let moves = &solution.principal_variation;
+        assert_eq!(moves[0].played, Move::Pass);
+        // The opponent plays out while the Q is stuck, and gains twice its value
+        assert!(moves
+            .iter()
+            .step_by(2)
+            .all(|scored| scored.played == Move::Pass));
+        assert_eq!(moves.len() % 2, 0);
+        assert_eq!(solution.spread, line_spread(moves) - 2 * 10);
+        assert!(solution.spread <= -(2 * 10 + 2));
+    }
+
+    #[test]
+    fn test_both_stuck() {
+        let solution = solve("q", "v");
+        assert!(solution.is_exact);
+        assert_eq!(
+            solution.principal_variation,
+            vec![
+                ScoredMove {
+                    played: Move::Pass,
+                    score: 0
+                };
+                2
+            ]
+        );
+        assert_eq!(solution.spread, 4 - 10);
+        assert_eq!(solve("v", "q").spread, 10 - 4);
+    }
+
+    /// The value of a position by plain minimax, for checking the pruned search
+    fn minimax(board: &mut ScrabbleBoard, racks: &mut [ScrabbleRack; 2], passed: bool) -> i32 {
+        let ruleset = Ruleset::english();
+        let values = |racks: &[ScrabbleRack; 2]| {
+            [
+                racks[0].value(&ruleset.tile_set),
+                racks[1].value(&ruleset.tile_set),
+            ]
+        };
+        let generator = PlayGenerator {
+            dawg: load_dawg(),
+            ruleset: &ruleset,
+            checked_board: board.to_checked_board(load_dawg(), &ruleset.tile_set),
+            rack: racks[0].clone(),
+        };
+        let [mine, theirs] = values(racks);
+        let mut best = if passed {
+            theirs - mine
+        } else {
+            racks.swap(0, 1);
+            let reply = minimax(board, racks, true);
+            racks.swap(0, 1);
+            -reply
+        };
+        for scored in generator.plays() {
+            let (saved_board, saved_racks) = (board.clone(), racks.clone());
+            for tile in scored.play.placed_tiles(board).chars() {
+                racks[0].remove_tile(tile);
+            }
+            board.add_play(&scored.play);
+            let value = if racks[0].is_empty() {
+                scored.score + 2 * theirs
+            } else {
+                racks.swap(0, 1);
+                scored.score - minimax(board, racks, false)
+            };
+            *board = saved_board;
+            *racks = saved_racks;
+            best = best.max(value);
+        }
+        best
+    }
+
+    #[test]
+    fn test_matches_minimax() {
+        for (rack, opponent) in [("at", "e"), ("q", "ai"), ("ae", "rt")] {
+            let solution = solve(rack, opponent);
+            assert!(solution.is_exact);
+            let mut racks = [ScrabbleRack::new(rack), ScrabbleRack::new(opponent)];
+            let expected = minimax(&mut hello_board(), &mut racks, false);
+            assert_eq!(solution.spread, expected, "{} against {}", rack, opponent);
+        }
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let ruleset = Ruleset::english();
+        let solver = EndgameSolver {
+            dawg: load_dawg(),
+            ruleset: &ruleset,
+            max_depth: 1,
+        };
+        let solution = solver.solve(
+            &hello_board(),
+            &ScrabbleRack::new("aeiou"),
+            &ScrabbleRack::new("rt"),
+        );
+        assert!(!solution.is_exact);
+        assert_eq!(solution.depth, 1);
+        assert_eq!(solution.principal_variation.len(), 1);
+    }
+}
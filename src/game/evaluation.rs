@@ -0,0 +1,291 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::game::board::ScrabbleBoard;
+use crate::game::play_generation::ScoredMove;
+use crate::game::rack::ScrabbleRack;
+use crate::game::util::BLANK_TILE_CHAR;
+
+/// Judges moves by more than their score, so that e.g. an S or a blank isn't played away for a
+/// couple of points
+pub trait Evaluator {
+    /// What keeping `leave` on the rack is worth to the coming turns, in points
+    fn leave_value(&self, leave: &ScrabbleRack) -> f64;
+
+    /// The worth of a move that scores `score` and keeps `leave`, by default the sum of the two
+    fn equity(&self, score: i32, leave: &ScrabbleRack) -> f64 {
+        score as f64 + self.leave_value(leave)
+    }
+
+    /// The equity of `scored`, made from `rack` on `board`
+    fn move_equity(&self, board: &ScrabbleBoard, rack: &ScrabbleRack, scored: &ScoredMove) -> f64 {
+        self.equity(scored.score, &scored.leave(board, rack))
+    }
+}
+
+/// Ranks moves by score alone
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScoreOnly;
+
+impl Evaluator for ScoreOnly {
+    fn leave_value(&self, _leave: &ScrabbleRack) -> f64 {
+        0.0
+    }
+}
+
+/// A move along with its equity
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankedMove {
+    pub scored: ScoredMove,
+    pub equity: f64,
+}
+
+/// Orders `moves`, made from `rack` on `board`, best first by their equity under `evaluator`.
+/// Moves of equal equity keep their order.
+pub fn rank_moves<E: Evaluator + ?Sized>(
+    evaluator: &E,
+    board: &ScrabbleBoard,
+    rack: &ScrabbleRack,
+    moves: Vec<ScoredMove>,
+) -> Vec<RankedMove> {
+    let mut ranked: Vec<RankedMove> = moves
+        .into_iter()
+        .map(|scored| RankedMove {
+            equity: evaluator.move_equity(board, rack, &scored),
+            scored,
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.equity.partial_cmp(&a.equity).unwrap_or(Ordering::Equal));
+    ranked
+}
+
+#[derive(Debug)]
+pub enum LeaveError {
+    /// The given (one-based) line isn't a leave followed by its value
+    InvalidLine(usize),
+    Io(io::Error),
+}
+
+impl fmt::Display for LeaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LeaveError::InvalidLine(line) => write!(f, "Line {} is not a leave and value", line),
+            LeaveError::Io(error) => write!(f, "Couldn't read leaves: {}", error),
+        }
+    }
+}
+
+impl Error for LeaveError {}
+
+impl From<io::Error> for LeaveError {
+    fn from(error: io::Error) -> Self {
+        LeaveError::Io(error)
+    }
+}
+
+/// The values of leaves, keyed by their tiles in character order. Leaves missing from the table
+/// are worth nothing.
+#[derive(Debug, Clone, Default)]
+pub struct LeaveTable {
+    values: HashMap<String, f64>,
+}
+
+impl LeaveTable {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<LeaveTable, LeaveError> {
+        LeaveTable::from_text(&fs::read_to_string(path)?)
+    }
+
+    /// Reads a leave and its value from each non-empty line, separated by a comma or spaces, as
+    /// in `aeinst,12.5`. The tiles may be in any order, with `_` or `?` for a blank.
+    pub fn from_text(text: &str) -> Result<LeaveTable, LeaveError> {
+        let mut table = LeaveTable::default();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = || LeaveError::InvalidLine(index + 1);
+            let mut fields = line.split(|ch: char| ch == ',' || ch.is_whitespace());
+            let leave = fields.next().ok_or_else(invalid)?.replace('?', "_");
+            let value = fields
+                .find(|field| !field.is_empty())
+                .and_then(|field| field.parse::<f64>().ok())
+                .ok_or_else(invalid)?;
+            if !leave
+                .chars()
+                .all(|tile| tile == BLANK_TILE_CHAR || tile.is_lowercase())
+            {
+                return Err(invalid());
+            }
+            table.values.insert(LeaveTable::key(&leave), value);
+        }
+        Ok(table)
+    }
+
+    pub fn insert(&mut self, leave: &ScrabbleRack, value: f64) {
+        self.values.insert(LeaveTable::key(&leave.tiles()), value);
+    }
+
+    pub fn get(&self, leave: &ScrabbleRack) -> Option<f64> {
+        self.values.get(&LeaveTable::key(&leave.tiles())).copied()
+    }
+
+    fn key(tiles: &str) -> String {
+        let mut tiles: Vec<char> = tiles.chars().collect();
+        tiles.sort_unstable();
+        tiles.into_iter().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl Evaluator for LeaveTable {
+    fn leave_value(&self, leave: &ScrabbleRack) -> f64 {
+        self.get(leave).unwrap_or(0.0)
+    }
+}
+
+/// A rough leave value for when no table is at hand: blanks and an S are worth keeping, while
+/// duplicated letters, a Q without a U and too many vowels or consonants are not
+#[derive(Debug, Clone)]
+pub struct LeaveHeuristic {
+    /// The letters counted as vowels
+    pub vowels: String,
+}
+
+impl LeaveHeuristic {
+    const BLANK: f64 = 25.0;
+    const S: f64 = 8.0;
+    const DUPLICATE: f64 = -3.0;
+    const Q_WITHOUT_U: f64 = -7.0;
+    /// For each vowel more or fewer than two fifths of the letters
+    const IMBALANCE: f64 = -2.5;
+}
+
+impl Default for LeaveHeuristic {
+    fn default() -> LeaveHeuristic {
+        LeaveHeuristic {
+            vowels: "aeiou".to_string(),
+        }
+    }
+}
+
+impl Evaluator for LeaveHeuristic {
+    fn leave_value(&self, leave: &ScrabbleRack) -> f64 {
+        let tiles = leave.tiles();
+        let mut value = 0.0;
+        let mut counts: HashMap<char, usize> = HashMap::new();
+        for tile in tiles.chars() {
+            *counts.entry(tile).or_insert(0) += 1;
+        }
+        for (&tile, &count) in counts.iter() {
+            match tile {
+                BLANK_TILE_CHAR => value += LeaveHeuristic::BLANK * count as f64,
+                _ => value += LeaveHeuristic::DUPLICATE * (count - 1) as f64,
+            }
+        }
+        if counts.contains_key(&'s') {
+            value += LeaveHeuristic::S;
+        }
+        if counts.contains_key(&'q') && !counts.contains_key(&'u') {
+            value += LeaveHeuristic::Q_WITHOUT_U;
+        }
+        let letters = tiles.chars().filter(|&tile| tile != BLANK_TILE_CHAR);
+        let (n_vowels, n_letters) = letters.fold((0, 0), |(n_vowels, n_letters), tile| {
+            (
+                n_vowels + self.vowels.contains(tile) as usize,
+                n_letters + 1,
+            )
+        });
+        let imbalance = (n_vowels as f64 - 0.4 * n_letters as f64).abs();
+        // Within one vowel of the ideal is balanced enough
+        value += LeaveHeuristic::IMBALANCE * (imbalance - 1.0).max(0.0);
+        value
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::state::Move;
+    use crate::game::util::{Direction, Position};
+    use crate::game::ScrabblePlay;
+
+    #[test]
+    fn test_leave_table() {
+        let table = LeaveTable::from_text("?s,40.5\nqu -2\n\neinst 20\n").unwrap();
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.leave_value(&ScrabbleRack::new("s_")), 40.5);
+        assert_eq!(table.leave_value(&ScrabbleRack::new("uq")), -2.0);
+        assert_eq!(table.leave_value(&ScrabbleRack::new("tsnie")), 20.0);
+        assert_eq!(table.leave_value(&ScrabbleRack::new("zz")), 0.0);
+        assert_eq!(table.equity(10, &ScrabbleRack::new("s_")), 50.5);
+
+        assert!(matches!(
+            LeaveTable::from_text("aei\n"),
+            Err(LeaveError::InvalidLine(1))
+        ));
+        assert!(matches!(
+            LeaveTable::from_text("a 1\nA 2\n"),
+            Err(LeaveError::InvalidLine(2))
+        ));
+        assert!(matches!(
+            LeaveTable::from_path("no/such/leaves.csv"),
+            Err(LeaveError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_leave_heuristic() {
+        let heuristic = LeaveHeuristic::default();
+        let value = |tiles| heuristic.leave_value(&ScrabbleRack::new(tiles));
+        assert_eq!(value(""), 0.0);
+        assert!(value("_") > value("s"));
+        assert!(value("s") > value("t"));
+        assert!(value("qu") > value("qt"));
+        assert!(value("ers") > value("eees"));
+        assert!(value("aeit") > value("aeio"));
+        assert!(value("rst") > value("rstv"));
+    }
+
+    #[test]
+    fn test_rank_moves() {
+        let board = ScrabbleBoard::default();
+        let rack = ScrabbleRack::new("aest");
+        let place = |word: &str, score| ScoredMove {
+            played: Move::Place(ScrabblePlay {
+                start: Position { row: 7, col: 7 },
+                direction: Direction::Horizontal,
+                word: word.to_string(),
+            }),
+            score,
+        };
+        let moves = vec![
+            place("eats", 10),
+            place("eat", 9),
+            ScoredMove {
+                played: Move::Pass,
+                score: 0,
+            },
+        ];
+        let by_score = rank_moves(&ScoreOnly, &board, &rack, moves.clone());
+        assert_eq!(by_score[0].scored, moves[0]);
+        assert_eq!(by_score[0].equity, 10.0);
+
+        // Keeping the S is worth more than the point it scores
+        let by_leave = rank_moves(&LeaveHeuristic::default(), &board, &rack, moves.clone());
+        assert_eq!(by_leave[0].scored, moves[1]);
+        assert_eq!(by_leave[0].equity, 9.0 + LeaveHeuristic::S);
+    }
+}
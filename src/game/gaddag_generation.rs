@@ -0,0 +1,235 @@
+use crate::dawg::{DawgEdge, DawgNodeIndex};
+use crate::gaddag::Gaddag;
+use crate::game::play_generation::{GenerationAisle, ScoredMove, ScoredScrabblePlay};
+use crate::game::ruleset::Ruleset;
+use crate::game::util::{placed_tile, BLANK_TILE_CHAR};
+use crate::game::{CheckedScrabbleBoard, ScrabbleRack};
+
+/// Generates the same plays as `PlayGenerator` using Gordon's algorithm over a `Gaddag`.
+///
+/// Each word is built outwards from its anchor: leftwards while following the reversed prefix,
+/// then rightwards after the separator, so no left parts have to be tried and discarded. Like
+/// `PlayGenerator`, a play is found from the leftmost anchor it places a tile on, so tiles are
+/// never placed on other anchors while moving left.
+pub struct GaddagPlayGenerator<'a> {
+    pub gaddag: &'a Gaddag,
+    pub ruleset: &'a Ruleset,
+    pub checked_board: CheckedScrabbleBoard,
+    pub rack: ScrabbleRack,
+}
+
+impl<'a> GaddagPlayGenerator<'a> {
+    pub fn plays(&self) -> Vec<ScoredScrabblePlay> {
+        let mut plays: Vec<ScoredScrabblePlay> = Vec::new();
+        for aisle in GenerationAisle::all(&self.checked_board).iter() {
+            let mut state = GaddagState {
+                plays: Vec::new(),
+                rack: self.rack.clone(),
+                word: aisle.squares.iter().map(|square| square.tile).collect(),
+            };
+            for (anchor_index, square) in aisle.squares.iter().enumerate() {
+                if square.is_anchor {
+                    let anchor = GaddagAnchor {
+                        gaddag: self.gaddag,
+                        ruleset: self.ruleset,
+                        aisle,
+                        anchor_index,
+                    };
+                    anchor.add_plays(&mut state, anchor_index, self.gaddag.root());
+                }
+            }
+            plays.append(&mut state.plays);
+        }
+        plays
+    }
+
+    /// Every legal move, as in `PlayGenerator::moves`
+    pub fn moves(&self, bag_len: usize) -> Vec<ScoredMove> {
+        let mut moves: Vec<ScoredMove> = self.plays().into_iter().map(ScoredMove::from).collect();
+        moves.extend(ScoredMove::non_placements(
+            self.ruleset,
+            &self.rack,
+            bag_len,
+        ));
+        moves
+    }
+}
+
+struct GaddagState {
+    plays: Vec<ScoredScrabblePlay>,
+    rack: ScrabbleRack,
+    /// The tiles of the aisle, including those placed so far
+    word: Vec<Option<char>>,
+}
+
+struct GaddagAnchor<'a> {
+    gaddag: &'a Gaddag,
+    ruleset: &'a Ruleset,
+    aisle: &'a GenerationAisle,
+    anchor_index: usize,
+}
+
+impl<'a> GaddagAnchor<'a> {
+    /// Covers the square at `index` (left of the anchor while following a reversed prefix,
+    /// right of it after the separator) with an edge leaving `node`
+    fn add_plays(&self, state: &mut GaddagState, index: usize, node: DawgNodeIndex) {
+        let square = &self.aisle.squares[index];
+        if let Some(ch) = square.tile {
+            let letter = self.gaddag.alphabet().letter(ch);
+            if let Some(edge) = letter.and_then(|letter| self.gaddag.leaving_edge(node, letter)) {
+                self.go_on(state, index, &edge);
+            }
+        } else if index >= self.anchor_index || !square.is_anchor {
+            // The separator isn't a letter of the alphabet, so the rack can't play it
+            let letters = square.compatible_letters() & state.rack.playable_letters();
+            self.gaddag.apply_to_child_edges_in(node, letters, |edge| {
+                let letter = self.gaddag.alphabet().symbol(edge.letter);
+                if state.rack.remove_letter(edge.letter) {
+                    state.word[index] = Some(letter);
+                    self.go_on(state, index, edge);
+                    state.word[index] = None;
+                    state.rack.add_letter(edge.letter);
+                }
+                if state.rack.remove_blank() {
+                    state.word[index] = Some(placed_tile(BLANK_TILE_CHAR, letter));
+                    self.go_on(state, index, edge);
+                    state.word[index] = None;
+                    state.rack.add_blank();
+                }
+            });
+        }
+    }
+
+    fn go_on(&self, state: &mut GaddagState, index: usize, edge: &DawgEdge) {
+        let aisle_len = self.aisle.squares.len();
+        if index <= self.anchor_index {
+            let left_is_open = index == 0 || state.word[index - 1].is_none();
+            let right_is_open =
+                self.anchor_index + 1 == aisle_len || state.word[self.anchor_index + 1].is_none();
+            if edge.word_terminator && left_is_open && right_is_open {
+                self.add_play(state, index, self.anchor_index + 1);
+            }
+            if edge.target.is_null() {
+                return;
+            }
+            if index > 0 {
+                self.add_plays(state, index - 1, edge.target);
+            }
+            if left_is_open && self.anchor_index + 1 < aisle_len {
+                let separator = self.gaddag.separator();
+                if let Some(separator) = self.gaddag.leaving_edge(edge.target, separator) {
+                    if separator.target.is_some() {
+                        self.add_plays(state, self.anchor_index + 1, separator.target);
+                    }
+                }
+            }
+        } else {
+            let right_is_open = index + 1 == aisle_len || state.word[index + 1].is_none();
+            if edge.word_terminator && right_is_open {
+                let start = self.word_start(state);
+                self.add_play(state, start, index + 1);
+            }
+            if edge.target.is_some() && index + 1 < aisle_len {
+                self.add_plays(state, index + 1, edge.target);
+            }
+        }
+    }
+
+    /// The first square of the word through the anchor, once the left part is complete
+    fn word_start(&self, state: &GaddagState) -> usize {
+        let mut start = self.anchor_index;
+        while start > 0 && state.word[start - 1].is_some() {
+            start -= 1;
+        }
+        start
+    }
+
+    fn add_play(&self, state: &mut GaddagState, start: usize, end: usize) {
+        let word: String = state.word[start..end]
+            .iter()
+            .map(|tile| tile.unwrap())
+            .collect();
+        let play = self.aisle.scored_play(self.ruleset, start, word);
+        state.plays.push(play);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+    use crate::alphabet::Alphabet;
+    use crate::dawg::Dawg;
+    use crate::game::play_generation::ScrabblePlay;
+    use crate::game::util::{Direction, Position};
+    use crate::game::{BoardLayout, PlayGenerator, ScrabbleBoard};
+
+    /// Compares the generators on the words of up to six letters, to keep the GADDAG quick to
+    /// build in debug builds
+    #[test]
+    fn test_same_plays_as_dawg() {
+        let words = fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/assets/scrabble_words.txt"
+        ))
+        .unwrap();
+        let words = words.lines().filter(|word| word.len() <= 6);
+        let dawg = Dawg::from_word_list(Alphabet::english(), words).unwrap();
+        let gaddag = Gaddag::from_dawg(&dawg);
+        let ruleset = Ruleset::english();
+
+        let mut board = ScrabbleBoard::default();
+        let mut boards = vec![board.clone()];
+        for (row, col, direction, word) in [
+            (7, 5, Direction::Horizontal, "hello"),
+            (5, 8, Direction::Vertical, "sold"),
+            (3, 6, Direction::Horizontal, "zaBe"),
+            (9, 10, Direction::Vertical, "kites"),
+        ] {
+            board.add_play(&ScrabblePlay {
+                start: Position { row, col },
+                direction,
+                word: word.to_string(),
+            });
+            boards.push(board.clone());
+        }
+        let mut super_board = ScrabbleBoard::new(BoardLayout::super_scrabble());
+        super_board.add_play(&ScrabblePlay {
+            start: Position { row: 10, col: 19 },
+            direction: Direction::Horizontal,
+            word: "at".to_string(),
+        });
+        boards.push(super_board);
+
+        for board in boards.iter() {
+            for rack in ["abcdefg", "retains", "qu_", "_eio", "ee_ls", "s"] {
+                let checked_board = board.to_checked_board(&dawg, &ruleset.tile_set);
+                let mut expected = PlayGenerator {
+                    dawg: &dawg,
+                    ruleset: &ruleset,
+                    checked_board: checked_board.clone(),
+                    rack: ScrabbleRack::new(rack),
+                }
+                .plays();
+                let mut actual = GaddagPlayGenerator {
+                    gaddag: &gaddag,
+                    ruleset: &ruleset,
+                    checked_board,
+                    rack: ScrabbleRack::new(rack),
+                }
+                .plays();
+                assert_eq!(
+                    actual.len(),
+                    expected.len(),
+                    "{}\n{}",
+                    rack,
+                    board.display()
+                );
+                expected.sort();
+                actual.sort();
+                assert_eq!(actual, expected);
+            }
+        }
+    }
+}
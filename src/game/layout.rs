@@ -0,0 +1,329 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::game::scoring::ScoreModifier;
+use crate::game::util::{Direction, Position};
+
+const STANDARD_LAYOUT: &str = "\
+T..d...T...d..T
+.D...t...t...D.
+..D...d.d...D..
+d..D...d...D..d
+....D.....D....
+.t...t...t...t.
+..d...d.d...d..
+T..d...D...d..T
+..d...d.d...d..
+.t...t...t...t.
+....D.....D....
+d..D...d...D..d
+..D...d.d...D..
+.D...t...t...D.
+T..d...T...d..T
+";
+
+const SUPER_LAYOUT: &str = "\
+Q..d...T..d..T...d..Q
+.D...t.........t...D.
+..D.q...........q.D..
+d..T..d...T...d..T..d
+..q.D...t...t...D.q..
+.t...D...d.d...D...t.
+...d..D...d...D..d...
+T......D.....D......T
+....t...t...t...t....
+.....d...d.d...d.....
+d..T..d...D...d..T..d
+.....d...d.d...d.....
+....t...t...t...t....
+T......D.....D......T
+...d..D...d...D..d...
+.t...D...d.d...D...t.
+..q.D...t...t...D.q..
+d..T..d...T...d..T..d
+..D.q...........q.D..
+.D...t.........t...D.
+Q..d...T..d..T...d..Q
+";
+
+const WWF_LAYOUT: &str = "\
+...T..t.t..T...
+..d..D...D..d..
+.d..d.....d..d.
+T..t...D...t..T
+..d...d.d...d..
+.D...t...t...D.
+t...d.....d...t
+...D.......D...
+t...d.....d...t
+.D...t...t...D.
+..d...d.d...d..
+T..t...D...t..T
+.d..d.....d..d.
+..d..D...D..d..
+...T..t.t..T...
+";
+
+/// The dimensions of a board, its premium squares, and the square the first play must cover.
+///
+/// Layouts can be read from text with one line per row of the board, using `.` for a plain
+/// square, `d`/`t`/`q` for double/triple/quadruple letter and `D`/`T`/`Q` for double/triple/
+/// quadruple word squares. Blank lines and lines starting with `#` are ignored, and an optional
+/// `start <row> <col>` line sets the start square, which otherwise is the centre of the board.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardLayout {
+    rows: usize,
+    cols: usize,
+    premiums: Vec<ScoreModifier>,
+    start: Position,
+}
+
+#[derive(Debug)]
+pub enum LayoutError {
+    Empty,
+    /// The given (zero-based) row doesn't have as many squares as the first row
+    RaggedRow(usize),
+    InvalidSquare(char),
+    InvalidStart(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LayoutError::Empty => write!(f, "The layout has no squares"),
+            LayoutError::RaggedRow(row) => write!(f, "Row {} has the wrong number of squares", row),
+            LayoutError::InvalidSquare(ch) => write!(f, "Invalid square '{}'", ch),
+            LayoutError::InvalidStart(line) => write!(f, "Invalid start square: '{}'", line),
+            LayoutError::Io(error) => write!(f, "Couldn't read layout: {}", error),
+        }
+    }
+}
+
+impl Error for LayoutError {}
+
+impl From<io::Error> for LayoutError {
+    fn from(error: io::Error) -> Self {
+        LayoutError::Io(error)
+    }
+}
+
+impl BoardLayout {
+    /// The standard 15x15 Scrabble board
+    pub fn standard() -> BoardLayout {
+        BoardLayout::from_text(STANDARD_LAYOUT).unwrap()
+    }
+
+    /// The 21x21 Super Scrabble board, which adds quadruple letter and word squares
+    pub fn super_scrabble() -> BoardLayout {
+        BoardLayout::from_text(SUPER_LAYOUT).unwrap()
+    }
+
+    /// A 15x15 Words-With-Friends-style board, whose start square carries no premium
+    pub fn wwf() -> BoardLayout {
+        BoardLayout::from_text(WWF_LAYOUT).unwrap()
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<BoardLayout, LayoutError> {
+        BoardLayout::from_text(&fs::read_to_string(path)?)
+    }
+
+    pub fn from_text(text: &str) -> Result<BoardLayout, LayoutError> {
+        let mut premiums = Vec::new();
+        let mut cols = 0;
+        let mut rows = 0;
+        let mut start = None;
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with("start") {
+                start = Some(BoardLayout::parse_start(line)?);
+                continue;
+            }
+            let row_len = line.chars().count();
+            if rows == 0 {
+                cols = row_len;
+            } else if row_len != cols {
+                return Err(LayoutError::RaggedRow(rows));
+            }
+            for ch in line.chars() {
+                premiums.push(BoardLayout::parse_square(ch)?);
+            }
+            rows += 1;
+        }
+        if premiums.is_empty() {
+            return Err(LayoutError::Empty);
+        }
+        let start = start.unwrap_or(Position {
+            row: rows / 2,
+            col: cols / 2,
+        });
+        if start.row >= rows || start.col >= cols {
+            return Err(LayoutError::InvalidStart(format!("{:?}", start)));
+        }
+        Ok(BoardLayout {
+            rows,
+            cols,
+            premiums,
+            start,
+        })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// The square the first play of the game must cover
+    pub fn start(&self) -> Position {
+        self.start
+    }
+
+    /// Whether the board is its own mirror image across the diagonal through the start square,
+    /// so that every play on the empty board has an equivalent in the other direction
+    pub fn is_symmetric(&self) -> bool {
+        self.rows == self.cols
+            && self.start.row == self.start.col
+            && (0..self.rows).all(|row| {
+                (0..row).all(|col| {
+                    self.modifier(Position { row, col })
+                        == self.modifier(Position { row: col, col: row })
+                })
+            })
+    }
+
+    pub fn n_squares(&self) -> usize {
+        self.rows * self.cols
+    }
+
+    /// The number of squares along an aisle in `direction`
+    pub fn aisle_len(&self, direction: Direction) -> usize {
+        match direction {
+            Direction::Horizontal => self.cols,
+            Direction::Vertical => self.rows,
+        }
+    }
+
+    /// The number of aisles running in `direction`
+    pub fn n_aisles(&self, direction: Direction) -> usize {
+        match direction {
+            Direction::Horizontal => self.rows,
+            Direction::Vertical => self.cols,
+        }
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        position.row < self.rows && position.col < self.cols
+    }
+
+    pub fn modifier(&self, position: Position) -> ScoreModifier {
+        self.premiums[self.square_index(position)]
+    }
+
+    /// The index of `position` in row-major order
+    pub fn square_index(&self, position: Position) -> usize {
+        position.row * self.cols + position.col
+    }
+
+    fn parse_square(ch: char) -> Result<ScoreModifier, LayoutError> {
+        Ok(match ch {
+            '.' => ScoreModifier::Plain,
+            'd' => ScoreModifier::DoubleLetter,
+            't' => ScoreModifier::TripleLetter,
+            'q' => ScoreModifier::QuadrupleLetter,
+            'D' => ScoreModifier::DoubleWord,
+            'T' => ScoreModifier::TripleWord,
+            'Q' => ScoreModifier::QuadrupleWord,
+            ch => return Err(LayoutError::InvalidSquare(ch)),
+        })
+    }
+
+    fn parse_start(line: &str) -> Result<Position, LayoutError> {
+        let invalid = || LayoutError::InvalidStart(line.to_string());
+        let coordinates: Vec<usize> = line["start".len()..]
+            .split_whitespace()
+            .map(|coordinate| coordinate.parse().map_err(|_| invalid()))
+            .collect::<Result<_, _>>()?;
+        match coordinates[..] {
+            [row, col] => Ok(Position { row, col }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl Default for BoardLayout {
+    fn default() -> BoardLayout {
+        BoardLayout::standard()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_presets() {
+        let standard = BoardLayout::standard();
+        assert_eq!((standard.rows(), standard.cols()), (15, 15));
+        assert_eq!(standard.start(), Position { row: 7, col: 7 });
+        assert_eq!(
+            standard.modifier(standard.start()),
+            ScoreModifier::DoubleWord
+        );
+        assert_eq!(
+            standard.modifier(Position { row: 0, col: 0 }),
+            ScoreModifier::TripleWord
+        );
+
+        let super_scrabble = BoardLayout::super_scrabble();
+        assert_eq!((super_scrabble.rows(), super_scrabble.cols()), (21, 21));
+        assert_eq!(super_scrabble.start(), Position { row: 10, col: 10 });
+        assert_eq!(
+            super_scrabble.modifier(Position { row: 20, col: 0 }),
+            ScoreModifier::QuadrupleWord
+        );
+
+        let wwf = BoardLayout::wwf();
+        assert_eq!(wwf.modifier(wwf.start()), ScoreModifier::Plain);
+        assert!(standard.is_symmetric() && super_scrabble.is_symmetric() && wwf.is_symmetric());
+    }
+
+    #[test]
+    fn test_from_text() {
+        let layout = BoardLayout::from_text("# A tiny board\nT.d\n.D.\nd.T\nstart 0 2\n").unwrap();
+        assert_eq!((layout.rows(), layout.cols()), (3, 3));
+        assert_eq!(layout.start(), Position { row: 0, col: 2 });
+        assert_eq!(
+            layout.modifier(Position { row: 2, col: 0 }),
+            ScoreModifier::DoubleLetter
+        );
+        assert!(!layout.is_symmetric());
+    }
+
+    #[test]
+    fn test_from_text_errors() {
+        assert!(matches!(
+            BoardLayout::from_text("# nothing"),
+            Err(LayoutError::Empty)
+        ));
+        assert!(matches!(
+            BoardLayout::from_text("...\n..\n"),
+            Err(LayoutError::RaggedRow(1))
+        ));
+        assert!(matches!(
+            BoardLayout::from_text("..x\n"),
+            Err(LayoutError::InvalidSquare('x'))
+        ));
+        assert!(matches!(
+            BoardLayout::from_text("...\nstart 3 0\n"),
+            Err(LayoutError::InvalidStart(_))
+        ));
+    }
+}
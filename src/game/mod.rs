@@ -1,13 +1,43 @@
-pub use crate::game::board::{CheckedScrabbleBoard, ScrabbleBoard, BOARD_SIZE};
-pub use crate::game::checked_square::{CheckedAisleSquare, CheckedBoardSquare};
-pub use crate::game::play_generation::{PlayGenerator, ScoredScrabblePlay, ScrabblePlay};
+pub use crate::game::bag::TileBag;
+pub use crate::game::board::{CheckedScrabbleBoard, ScrabbleBoard};
+pub use crate::game::checked_square::CheckedAisleSquare;
+pub(crate) use crate::game::cross_checks::CrossChecks;
+pub use crate::game::endgame::{EndgameSolution, EndgameSolver};
+pub use crate::game::evaluation::{
+    rank_moves, Evaluator, LeaveError, LeaveHeuristic, LeaveTable, RankedMove, ScoreOnly,
+};
+pub use crate::game::gaddag_generation::GaddagPlayGenerator;
+pub use crate::game::layout::{BoardLayout, LayoutError};
+pub use crate::game::play_generation::{
+    PlayGenerator, ScoredMove, ScoredScrabblePlay, ScrabblePlay,
+};
+pub use crate::game::pre_endgame::{
+    PreEndgameError, PreEndgameMove, PreEndgameSolver, MAX_PRE_ENDGAME_BAG,
+};
 pub use crate::game::rack::ScrabbleRack;
+pub use crate::game::ruleset::{Ruleset, TileSet};
+pub use crate::game::scoring::{PlayBreakdown, ScoreModifier, WordScore};
+pub use crate::game::simulation::{SimulatedMove, SimulationSettings, Simulator};
+pub use crate::game::state::{Game, GameError, Move, Player, Turn};
+pub(crate) use crate::game::util::placed_tile;
 pub use crate::game::util::{Direction, Position};
+pub use crate::game::validation::{PlayError, ValidatedPlay};
 
+mod bag;
 mod board;
 mod checked_square;
 mod cross_checks;
+mod endgame;
+mod evaluation;
+mod gaddag_generation;
+mod layout;
 mod play_generation;
+mod pre_endgame;
 mod rack;
+mod rng;
+mod ruleset;
 mod scoring;
+mod simulation;
+mod state;
 mod util;
+mod validation;
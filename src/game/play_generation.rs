@@ -1,7 +1,10 @@
 use crate::dawg::{Dawg, DawgEdge, DawgNodeIndex};
+use crate::game::board::ScrabbleBoard;
+use crate::game::ruleset::Ruleset;
 use crate::game::scoring::score_play;
-use crate::game::util::{Direction, Position};
-use crate::game::{CheckedAisleSquare, CheckedScrabbleBoard, ScrabbleRack, BOARD_SIZE};
+use crate::game::state::Move;
+use crate::game::util::{placed_tile, rack_tile, Direction, Position, BLANK_TILE_CHAR};
+use crate::game::{CheckedAisleSquare, CheckedScrabbleBoard, ScrabbleRack};
 
 #[derive(Debug, Clone, PartialOrd, PartialEq, Ord, Eq)]
 pub struct ScoredScrabblePlay {
@@ -9,6 +12,61 @@ pub struct ScoredScrabblePlay {
     pub score: i32,
 }
 
+/// Any move a player can make on their turn, with the score it earns
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoredMove {
+    pub played: Move,
+    pub score: i32,
+}
+
+impl ScoredMove {
+    /// Every distinct exchange of `rack`, if the bag holds enough tiles to allow one, then a pass
+    pub fn non_placements(
+        ruleset: &Ruleset,
+        rack: &ScrabbleRack,
+        bag_len: usize,
+    ) -> Vec<ScoredMove> {
+        let mut moves = Vec::new();
+        if bag_len >= ruleset.rack_size {
+            for tiles in rack.exchanges() {
+                moves.push(ScoredMove {
+                    played: Move::Exchange(tiles),
+                    score: 0,
+                });
+            }
+        }
+        moves.push(ScoredMove {
+            played: Move::Pass,
+            score: 0,
+        });
+        moves
+    }
+
+    /// The tiles left on `rack` after making the move on `board`, before drawing
+    pub fn leave(&self, board: &ScrabbleBoard, rack: &ScrabbleRack) -> ScrabbleRack {
+        let mut leave = rack.clone();
+        let used = match &self.played {
+            Move::Place(play) => play.placed_tiles(board),
+            Move::Exchange(tiles) => tiles.clone(),
+            Move::Pass => String::new(),
+        };
+        for tile in used.chars() {
+            leave.remove_tile(tile);
+        }
+        leave
+    }
+}
+
+impl From<ScoredScrabblePlay> for ScoredMove {
+    fn from(scored: ScoredScrabblePlay) -> ScoredMove {
+        ScoredMove {
+            played: Move::Place(scored.play),
+            score: scored.score,
+        }
+    }
+}
+
+/// A word placed on the board. Letters played from blanks are uppercase in `word`.
 #[derive(Debug, Clone, PartialOrd, PartialEq, Ord, Eq)]
 pub struct ScrabblePlay {
     pub start: Position,
@@ -16,8 +74,44 @@ pub struct ScrabblePlay {
     pub word: String,
 }
 
+impl ScrabblePlay {
+    /// The board positions covered by the word, in order
+    pub fn positions(&self) -> impl Iterator<Item = Position> + '_ {
+        let direction = self.direction;
+        let aisle = self.start.aisle(direction);
+        let first = self.start.cross(direction);
+        (0..self.word.chars().count())
+            .map(move |offset| Position::from_aisle_cross(direction, aisle, first + offset))
+    }
+
+    /// The rack tiles the play puts on `board`, i.e. those for the squares still empty
+    pub fn placed_tiles(&self, board: &ScrabbleBoard) -> String {
+        self.positions()
+            .zip(self.word.chars())
+            .filter(|&(position, _)| board[position].is_none())
+            .map(|(_, tile)| rack_tile(tile))
+            .collect()
+    }
+
+    /// Scores the play against the board it is about to be placed on; assumes the play is legal
+    pub fn score(&self, checked_board: &CheckedScrabbleBoard, ruleset: &Ruleset) -> i32 {
+        let aisle = GenerationAisle::new(
+            checked_board,
+            self.direction,
+            self.start.aisle(self.direction),
+        );
+        score_play(
+            &aisle,
+            ruleset,
+            self.start.cross(self.direction),
+            &self.word,
+        )
+    }
+}
+
 pub struct PlayGenerator<'a> {
-    pub dawg: &'a Dawg,
+    pub dawg: &'a Dawg<'a>,
+    pub ruleset: &'a Ruleset,
     pub checked_board: CheckedScrabbleBoard,
     pub rack: ScrabbleRack,
 }
@@ -29,7 +123,8 @@ impl<'a> PlayGenerator<'a> {
             for (anchor_index, tile) in aisle.squares.iter().enumerate() {
                 if tile.is_anchor {
                     let solving_anchor = GenerationAnchor {
-                        dawg: &self.dawg,
+                        dawg: self.dawg,
+                        ruleset: self.ruleset,
                         aisle,
                         anchor_index,
                     };
@@ -40,20 +135,20 @@ impl<'a> PlayGenerator<'a> {
         plays
     }
 
+    /// Every legal move: the plays, then the exchanges allowed with `bag_len` tiles in the bag,
+    /// then a pass
+    pub fn moves(&self, bag_len: usize) -> Vec<ScoredMove> {
+        let mut moves: Vec<ScoredMove> = self.plays().into_iter().map(ScoredMove::from).collect();
+        moves.extend(ScoredMove::non_placements(
+            self.ruleset,
+            &self.rack,
+            bag_len,
+        ));
+        moves
+    }
+
     fn generation_aisles(&self) -> Vec<GenerationAisle> {
-        let board = &self.checked_board;
-        let mut solving_rows: Vec<GenerationAisle> = Vec::with_capacity(2 * BOARD_SIZE);
-        for index in 0..BOARD_SIZE {
-            for &direction in Direction::iterator() {
-                let solving_row = GenerationAisle {
-                    direction,
-                    index,
-                    squares: board.aisle(direction, index),
-                };
-                solving_rows.push(solving_row);
-            }
-        }
-        solving_rows
+        GenerationAisle::all(&self.checked_board)
     }
 }
 
@@ -61,13 +156,49 @@ impl<'a> PlayGenerator<'a> {
 pub struct GenerationAisle {
     pub direction: Direction,
     pub index: usize,
-    pub squares: [CheckedAisleSquare; BOARD_SIZE],
+    pub squares: Vec<CheckedAisleSquare>,
 }
 
 impl GenerationAisle {
-    pub fn scored_play(&self, start_word_index: usize, word: String) -> ScoredScrabblePlay {
+    pub fn new(
+        board: &CheckedScrabbleBoard,
+        direction: Direction,
+        index: usize,
+    ) -> GenerationAisle {
+        GenerationAisle {
+            direction,
+            index,
+            squares: board.aisle(direction, index),
+        }
+    }
+
+    /// Every aisle plays can be generated along
+    pub fn all(board: &CheckedScrabbleBoard) -> Vec<GenerationAisle> {
+        let layout = &board.layout;
+        let n_aisles = layout.rows() + layout.cols();
+        let mut solving_rows: Vec<GenerationAisle> = Vec::with_capacity(n_aisles);
+        // Opening plays on a symmetric board would otherwise all be found again transposed
+        let directions = if board.is_empty() && layout.is_symmetric() {
+            &[Direction::Horizontal][..]
+        } else {
+            &[Direction::Horizontal, Direction::Vertical][..]
+        };
+        for &direction in directions {
+            for index in 0..layout.n_aisles(direction) {
+                solving_rows.push(GenerationAisle::new(board, direction, index));
+            }
+        }
+        solving_rows
+    }
+
+    pub fn scored_play(
+        &self,
+        ruleset: &Ruleset,
+        start_word_index: usize,
+        word: String,
+    ) -> ScoredScrabblePlay {
         let start = self.position(start_word_index);
-        let score = score_play(&self, start_word_index, &word);
+        let score = score_play(self, ruleset, start_word_index, &word);
         let play = ScrabblePlay {
             start,
             direction: self.direction,
@@ -88,7 +219,8 @@ struct GenerationState {
 }
 
 struct GenerationAnchor<'a> {
-    dawg: &'a Dawg,
+    dawg: &'a Dawg<'a>,
+    ruleset: &'a Ruleset,
     aisle: &'a GenerationAisle,
     anchor_index: usize,
 }
@@ -134,7 +266,7 @@ impl<'a> GenerationAnchor<'a> {
     }
 
     fn initial_left_part(&self, left_part_start_index: usize) -> (String, DawgNodeIndex) {
-        let mut partial_word = String::with_capacity(BOARD_SIZE);
+        let mut partial_word = String::with_capacity(self.aisle.squares.len());
         let mut node = self.dawg.root();
         for square in self.aisle.squares[left_part_start_index..self.anchor_index].iter() {
             let ch = square.tile.unwrap();
@@ -142,7 +274,9 @@ impl<'a> GenerationAnchor<'a> {
             if node.is_some() {
                 node = self
                     .dawg
-                    .leaving_edge(node, ch)
+                    .alphabet
+                    .letter(ch)
+                    .and_then(|letter| self.dawg.leaving_edge(node, letter))
                     .map(|edge| edge.target)
                     .unwrap_or(DawgNodeIndex(0));
             }
@@ -163,14 +297,24 @@ impl<'a> GenerationAnchor<'a> {
     fn add_plays_for_left(&self, state: &mut GenerationState, node: DawgNodeIndex, limit: usize) {
         self.extend_right(state, node, self.anchor_index);
         if limit > 0 {
-            self.dawg.apply_to_child_edges(node, |edge| {
+            let playable = state.rack.playable_letters();
+            self.dawg.apply_to_child_edges_in(node, playable, |edge| {
                 let target = edge.target;
                 if target.is_some() {
-                    if let Ok(tile) = state.rack.take_tile(edge.letter) {
-                        state.partial_word.push(edge.letter);
+                    let letter = self.dawg.alphabet.symbol(edge.letter);
+                    if state.rack.remove_letter(edge.letter) {
+                        state.partial_word.push(letter);
                         self.add_plays_for_left(state, target, limit - 1);
                         state.partial_word.pop();
-                        state.rack.add_tile(tile);
+                        state.rack.add_letter(edge.letter);
+                    }
+                    if state.rack.remove_blank() {
+                        state
+                            .partial_word
+                            .push(placed_tile(BLANK_TILE_CHAR, letter));
+                        self.add_plays_for_left(state, target, limit - 1);
+                        state.partial_word.pop();
+                        state.rack.add_blank();
                     }
                 }
             });
@@ -183,21 +327,28 @@ impl<'a> GenerationAnchor<'a> {
         node: DawgNodeIndex,
         next_tile_index: usize,
     ) {
-        if next_tile_index >= BOARD_SIZE {
+        if next_tile_index >= self.aisle.squares.len() {
             return;
         }
         let next_square = &self.aisle.squares[next_tile_index];
         if let Some(ch) = next_square.tile {
-            if let Some(edge) = self.dawg.leaving_edge(node, ch) {
-                self.extend_using_edge(state, next_tile_index, edge);
+            let letter = self.dawg.alphabet.letter(ch);
+            if let Some(edge) = letter.and_then(|letter| self.dawg.leaving_edge(node, letter)) {
+                self.extend_using_edge(state, next_tile_index, &edge, ch);
             }
         } else {
-            self.dawg.apply_to_child_edges(node, |edge| {
-                if let Ok(tile) = state.rack.take_tile(edge.letter) {
-                    if next_square.is_compatible(edge.letter) {
-                        self.extend_using_edge(state, next_tile_index, edge);
-                    }
-                    state.rack.add_tile(tile);
+            let letters = next_square.compatible_letters() & state.rack.playable_letters();
+            self.dawg.apply_to_child_edges_in(node, letters, |edge| {
+                let letter = self.dawg.alphabet.symbol(edge.letter);
+                if state.rack.remove_letter(edge.letter) {
+                    self.extend_using_edge(state, next_tile_index, edge, letter);
+                    state.rack.add_letter(edge.letter);
+                }
+                // Playing a blank instead of a held letter can change the score
+                if state.rack.remove_blank() {
+                    let placed = placed_tile(BLANK_TILE_CHAR, letter);
+                    self.extend_using_edge(state, next_tile_index, edge, placed);
+                    state.rack.add_blank();
                 }
             })
         }
@@ -208,8 +359,9 @@ impl<'a> GenerationAnchor<'a> {
         state: &mut GenerationState,
         placement_index: usize,
         edge: &DawgEdge,
+        placed: char,
     ) {
-        state.partial_word.push(edge.letter);
+        state.partial_word.push(placed);
         self.check_add_play(state, edge, placement_index + 1);
         let target = edge.target;
         if target.is_some() {
@@ -224,7 +376,8 @@ impl<'a> GenerationAnchor<'a> {
         edge: &DawgEdge,
         next_square_index: usize,
     ) {
-        if next_square_index < BOARD_SIZE && self.aisle.squares[next_square_index].tile.is_some() {
+        let aisle_len = self.aisle.squares.len();
+        if next_square_index < aisle_len && self.aisle.squares[next_square_index].tile.is_some() {
             return; // there is a tile in the next square, so this partial_word isn't a valid play
         }
         if next_square_index < self.anchor_index + 1 {
@@ -232,10 +385,10 @@ impl<'a> GenerationAnchor<'a> {
         }
 
         if edge.word_terminator {
-            let start = next_square_index - state.partial_word.len();
+            let start = next_square_index - state.partial_word.chars().count();
             let play = self
                 .aisle
-                .scored_play(start, state.partial_word.to_string());
+                .scored_play(self.ruleset, start, state.partial_word.to_string());
             state.plays.push(play)
         }
     }
@@ -0,0 +1,439 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+
+use crate::dawg::Dawg;
+use crate::game::board::ScrabbleBoard;
+use crate::game::endgame::EndgameSolver;
+use crate::game::play_generation::{PlayGenerator, ScoredMove};
+use crate::game::rack::ScrabbleRack;
+use crate::game::ruleset::Ruleset;
+use crate::game::state::{Move, MAX_SCORELESS_TURNS};
+
+/// The most tiles the bag can hold for a pre-endgame to be analysed
+pub const MAX_PRE_ENDGAME_BAG: usize = 6;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreEndgameError {
+    /// The unseen tiles leave this many in the bag once the opponent's rack is filled, which
+    /// isn't a pre-endgame
+    BagSize(usize),
+}
+
+impl fmt::Display for PreEndgameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PreEndgameError::BagSize(n) => write!(
+                f,
+                "{} tiles in the bag; a pre-endgame has 1 to {}",
+                n, MAX_PRE_ENDGAME_BAG
+            ),
+        }
+    }
+}
+
+impl Error for PreEndgameError {}
+
+/// A candidate move with its results over every way the unseen tiles could be split between the
+/// bag and the opponent's rack
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreEndgameMove {
+    pub scored: ScoredMove,
+    /// Whether the move draws every tile left in the bag, so that the endgame starts with the
+    /// opponent to move
+    pub empties_bag: bool,
+    /// The splits the player wins from, counting ties as half. Identical tiles count separately,
+    /// so each split is as likely as any other.
+    pub wins: f64,
+    pub n_splits: u64,
+    /// The player's final lead, averaged over the splits
+    pub expected_spread: f64,
+}
+
+/// Analyses positions with a few tiles in the bag, where the result depends on who draws what.
+///
+/// Every split of the unseen tiles into the opponent's rack and the bag is tried with each
+/// candidate move. Draws are then averaged over, both sides choose their moves knowing the
+/// racks, and once the bag is empty the game is played out by an `EndgameSolver`. As in the
+/// endgame, two passes in a row end the game, as do the usual run of scoreless turns.
+pub struct PreEndgameSolver<'a> {
+    pub dawg: &'a Dawg<'a>,
+    pub ruleset: &'a Ruleset,
+    /// How many of the highest scoring plays are tried on each turn before the bag is empty,
+    /// besides the best play placing each number of tiles, any exchanges and a pass
+    pub n_candidates: usize,
+    /// The search depth of the endgames
+    pub max_depth: usize,
+}
+
+impl<'a> PreEndgameSolver<'a> {
+    /// Analyses the moves of `rack` on `board` with `unseen` tiles in the bag and on the
+    /// opponent's rack, returning them with the most wins first. `spread` is the player's
+    /// current lead.
+    pub fn analyse(
+        &self,
+        board: &ScrabbleBoard,
+        rack: &ScrabbleRack,
+        unseen: &str,
+        spread: i32,
+    ) -> Result<Vec<PreEndgameMove>, PreEndgameError> {
+        let n_unseen = unseen.chars().count();
+        let bag_len = n_unseen.saturating_sub(self.ruleset.rack_size);
+        if bag_len == 0 || bag_len > MAX_PRE_ENDGAME_BAG {
+            return Err(PreEndgameError::BagSize(bag_len));
+        }
+        let splits = draws(unseen, self.ruleset.rack_size);
+        let mut analysed: Vec<PreEndgameMove> = self
+            .candidates(board, rack, bag_len)
+            .into_iter()
+            .map(|scored| PreEndgameMove {
+                empties_bag: n_placed(&scored, board) >= bag_len,
+                scored,
+                wins: 0.0,
+                n_splits: 0,
+                expected_spread: 0.0,
+            })
+            .collect();
+        let mut board = board.clone();
+        for (opponent, n_ways) in splits.iter() {
+            let bag = remove_tiles(unseen, opponent);
+            let racks = [rack.clone(), rack.with_tiles(opponent)];
+            for candidate in analysed.iter_mut() {
+                let value = self.move_value(&mut board, &racks, &bag, &candidate.scored, 0, false);
+                let final_spread = spread as f64 + value;
+                candidate.wins += *n_ways as f64
+                    * match final_spread.partial_cmp(&0.0) {
+                        Some(Ordering::Greater) => 1.0,
+                        Some(Ordering::Equal) => 0.5,
+                        _ => 0.0,
+                    };
+                candidate.n_splits += n_ways;
+                candidate.expected_spread += *n_ways as f64 * final_spread;
+            }
+        }
+        for candidate in analysed.iter_mut() {
+            candidate.expected_spread /= candidate.n_splits as f64;
+        }
+        analysed.sort_by(|a, b| {
+            b.wins
+                .total_cmp(&a.wins)
+                .then(b.expected_spread.total_cmp(&a.expected_spread))
+        });
+        Ok(analysed)
+    }
+
+    /// The highest scoring plays and the best play placing each number of tiles, as how many
+    /// tiles are drawn decides who empties the bag. Then the exchanges the bag allows, then a
+    /// pass.
+    fn candidates(
+        &self,
+        board: &ScrabbleBoard,
+        rack: &ScrabbleRack,
+        bag_len: usize,
+    ) -> Vec<ScoredMove> {
+        let generator = PlayGenerator {
+            dawg: self.dawg,
+            ruleset: self.ruleset,
+            checked_board: board.to_checked_board(self.dawg, &self.ruleset.tile_set),
+            rack: rack.clone(),
+        };
+        let mut plays = generator.plays();
+        plays.sort_by_key(|scored| -scored.score);
+        let mut has_n_placed = vec![false; rack.len() + 1];
+        let mut moves = Vec::new();
+        for (rank, scored) in plays.into_iter().enumerate() {
+            let n_placed = scored.play.placed_tiles(board).chars().count();
+            if rank < self.n_candidates || !has_n_placed[n_placed] {
+                has_n_placed[n_placed] = true;
+                moves.push(ScoredMove::from(scored));
+            }
+        }
+        moves.extend(ScoredMove::non_placements(self.ruleset, rack, bag_len));
+        moves
+    }
+
+    /// The value to the player to move of the position with `racks`, the mover's first, and
+    /// `bag`. `scoreless` is how many turns in a row have scored nothing, and `passed` whether
+    /// the last of them was a pass.
+    fn value(
+        &self,
+        board: &mut ScrabbleBoard,
+        racks: &[ScrabbleRack; 2],
+        bag: &str,
+        scoreless: usize,
+        passed: bool,
+    ) -> f64 {
+        // Only a play can draw the last tiles, so the endgame never starts just after a pass.
+        // A run of plays scoring nothing is forgotten there, as the endgame only ends on two
+        // passes in a row, which is a deliberate simplification for so rare a line.
+        if bag.is_empty() {
+            let solver = EndgameSolver {
+                dawg: self.dawg,
+                ruleset: self.ruleset,
+                max_depth: self.max_depth,
+            };
+            return solver.solve(board, &racks[0], &racks[1]).spread as f64;
+        }
+        let candidates = self.candidates(board, &racks[0], bag.chars().count());
+        candidates
+            .iter()
+            .map(|scored| self.move_value(board, racks, bag, scored, scoreless, passed))
+            .fold(f64::NEG_INFINITY, f64::max)
+    }
+
+    /// The value of making `scored` to the player to move, averaged over what they draw
+    fn move_value(
+        &self,
+        board: &mut ScrabbleBoard,
+        racks: &[ScrabbleRack; 2],
+        bag: &str,
+        scored: &ScoredMove,
+        scoreless: usize,
+        passed: bool,
+    ) -> f64 {
+        let scoreless = if scored.score == 0 { scoreless + 1 } else { 0 };
+        let leave = scored.leave(board, &racks[0]);
+        let (n_drawn, returned) = match &scored.played {
+            Move::Place(play) => (play.placed_tiles(board).chars().count(), ""),
+            Move::Exchange(tiles) => (tiles.chars().count(), tiles.as_str()),
+            Move::Pass if passed || scoreless >= MAX_SCORELESS_TURNS => {
+                return self.finished(racks);
+            }
+            Move::Pass => {
+                let swapped = [racks[1].clone(), racks[0].clone()];
+                return -self.value(board, &swapped, bag, scoreless, true);
+            }
+        };
+        let before = board.clone();
+        if let Move::Place(play) = &scored.played {
+            board.add_play(play);
+        }
+        let drawable = draws(bag, n_drawn.min(bag.chars().count()));
+        let total_ways: u64 = drawable.iter().map(|(_, n_ways)| n_ways).sum();
+        let mut expected = 0.0;
+        for (drawn, n_ways) in drawable.iter() {
+            let mut rack = leave.clone();
+            rack.add_tiles(drawn);
+            let mut rest = remove_tiles(bag, drawn);
+            rest.push_str(returned);
+            let swapped = [racks[1].clone(), rack];
+            let reply = if scoreless >= MAX_SCORELESS_TURNS {
+                self.finished(&swapped)
+            } else {
+                self.value(board, &swapped, &rest, scoreless, false)
+            };
+            expected += *n_ways as f64 * (scored.score as f64 - reply);
+        }
+        *board = before;
+        expected / total_ways as f64
+    }
+
+    /// The value to the player to move of the game ending without anyone going out
+    fn finished(&self, racks: &[ScrabbleRack; 2]) -> f64 {
+        let tile_set = &self.ruleset.tile_set;
+        (racks[1].value(tile_set) - racks[0].value(tile_set)) as f64
+    }
+}
+
+/// How many tiles `scored` takes from the rack, and so how many are drawn after it
+fn n_placed(scored: &ScoredMove, board: &ScrabbleBoard) -> usize {
+    match &scored.played {
+        Move::Place(play) => play.placed_tiles(board).chars().count(),
+        Move::Exchange(_) | Move::Pass => 0,
+    }
+}
+
+/// Every distinct group of `n` of `tiles`, with the number of ways of picking it when identical
+/// tiles are told apart
+fn draws(tiles: &str, n: usize) -> Vec<(String, u64)> {
+    let mut sorted: Vec<char> = tiles.chars().collect();
+    sorted.sort_unstable();
+    let mut counts: Vec<(char, usize)> = Vec::new();
+    for tile in sorted {
+        match counts.last_mut() {
+            Some((last, count)) if *last == tile => *count += 1,
+            _ => counts.push((tile, 1)),
+        }
+    }
+    let mut found = Vec::new();
+    add_draws(&counts, n, &mut String::new(), 1, &mut found);
+    found
+}
+
+fn add_draws(
+    counts: &[(char, usize)],
+    n: usize,
+    drawn: &mut String,
+    n_ways: u64,
+    found: &mut Vec<(String, u64)>,
+) {
+    let (&(tile, count), rest) = match counts.split_first() {
+        Some(first) => first,
+        None => {
+            if n == 0 {
+                found.push((drawn.clone(), n_ways));
+            }
+            return;
+        }
+    };
+    for k in 0..=count.min(n) {
+        let len = drawn.len();
+        for _ in 0..k {
+            drawn.push(tile);
+        }
+        add_draws(rest, n - k, drawn, n_ways * binomial(count, k), found);
+        drawn.truncate(len);
+    }
+}
+
+fn binomial(n: usize, k: usize) -> u64 {
+    (0..k).fold(1, |result, i| result * (n - i) as u64 / (i + 1) as u64)
+}
+
+/// `tiles` without one of each of `removed`
+fn remove_tiles(tiles: &str, removed: &str) -> String {
+    let mut rest: Vec<char> = tiles.chars().collect();
+    for tile in removed.chars() {
+        if let Some(index) = rest.iter().position(|&other| other == tile) {
+            rest.remove(index);
+        }
+    }
+    rest.into_iter().collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::play_generation::ScrabblePlay;
+    use crate::game::util::{Direction, Position};
+    use crate::loading::load_dawg;
+
+    fn hello_board() -> ScrabbleBoard {
+        let mut board = ScrabbleBoard::default();
+        board.add_play(&ScrabblePlay {
+            start: Position { row: 7, col: 7 },
+            direction: Direction::Horizontal,
+            word: "hello".to_string(),
+        });
+        board
+    }
+
+    /// English tiles on racks of two, so that positions are small enough to search exhaustively
+    fn small_racks() -> Ruleset {
+        Ruleset {
+            rack_size: 2,
+            ..Ruleset::english()
+        }
+    }
+
+    #[test]
+    fn test_draws() {
+        let found = draws("aab", 2);
+        assert_eq!(found, vec![("ab".to_string(), 2), ("aa".to_string(), 1)]);
+        let total: u64 = draws("aaabcc", 3).iter().map(|(_, n_ways)| n_ways).sum();
+        assert_eq!(total, binomial(6, 3));
+        assert_eq!(remove_tiles("aabc", "ca"), "ab");
+    }
+
+    #[test]
+    fn test_analyse() {
+        let ruleset = small_racks();
+        let solver = PreEndgameSolver {
+            dawg: load_dawg(),
+            ruleset: &ruleset,
+            n_candidates: 5,
+            max_depth: 4,
+        };
+        let rack = ScrabbleRack::new("as");
+        let analysed = solver.analyse(&hello_board(), &rack, "tqe", 0).unwrap();
+        // Three ways to pick the opponent's two tiles
+        assert!(analysed.iter().all(|analysed| analysed.n_splits == 3));
+        assert!(analysed
+            .iter()
+            .any(|analysed| analysed.scored.played == Move::Pass));
+        assert!(analysed.iter().any(|analysed| analysed.empties_bag));
+        assert!(analysed.windows(2).all(|pair| pair[0].wins >= pair[1].wins));
+        for analysed in analysed.iter() {
+            assert!((0.0..=3.0).contains(&analysed.wins));
+        }
+
+        // The two Qs count separately, so QE is the opponent's rack twice as often as QQ
+        let repeated = solver.analyse(&hello_board(), &rack, "qqe", 0).unwrap();
+        assert!(repeated.iter().all(|analysed| analysed.n_splits == 3));
+    }
+
+    #[test]
+    fn test_candidates() {
+        let ruleset = Ruleset::english();
+        let solver = PreEndgameSolver {
+            dawg: load_dawg(),
+            ruleset: &ruleset,
+            n_candidates: 1,
+            max_depth: 2,
+        };
+        let board = hello_board();
+        let candidates = solver.candidates(&board, &ScrabbleRack::new("abcdefg"), 3);
+        // The top play is the best of its number of tiles, so with one candidate there is a play
+        // of each number, both emptying the bag of three and not
+        let mut placed: Vec<usize> = candidates
+            .iter()
+            .filter(|scored| matches!(scored.played, Move::Place(_)))
+            .map(|scored| n_placed(scored, &board))
+            .collect();
+        assert!(placed.iter().any(|&n| n >= 3));
+        assert!(placed.iter().any(|&n| n < 3));
+        let n_plays = placed.len();
+        placed.sort_unstable();
+        placed.dedup();
+        assert_eq!(placed.len(), n_plays);
+    }
+
+    #[test]
+    fn test_exchange() {
+        // With racks of one tile, one tile in the bag allows an exchange
+        let ruleset = Ruleset {
+            rack_size: 1,
+            ..Ruleset::english()
+        };
+        let solver = PreEndgameSolver {
+            dawg: load_dawg(),
+            ruleset: &ruleset,
+            n_candidates: 5,
+            max_depth: 4,
+        };
+        let analysed = solver
+            .analyse(&hello_board(), &ScrabbleRack::new("q"), "sv", 0)
+            .unwrap();
+        let exchange = analysed
+            .iter()
+            .find(|analysed| analysed.scored.played == Move::Exchange("q".to_string()))
+            .unwrap();
+        assert_eq!(exchange.n_splits, 2);
+        // Swapping the stuck Q is better than passing with it
+        let pass = analysed
+            .iter()
+            .find(|analysed| analysed.scored.played == Move::Pass)
+            .unwrap();
+        assert!(exchange.expected_spread > pass.expected_spread);
+    }
+
+    #[test]
+    fn test_bag_size() {
+        let ruleset = Ruleset::english();
+        let solver = PreEndgameSolver {
+            dawg: load_dawg(),
+            ruleset: &ruleset,
+            n_candidates: 5,
+            max_depth: 4,
+        };
+        let rack = ScrabbleRack::new("abcdefg");
+        assert_eq!(
+            solver.analyse(&hello_board(), &rack, "abcdefg", 0),
+            Err(PreEndgameError::BagSize(0))
+        );
+        assert_eq!(
+            solver.analyse(&hello_board(), &rack, "abcdefghijklmn", 0),
+            Err(PreEndgameError::BagSize(7))
+        );
+    }
+}
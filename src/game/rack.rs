@@ -1,56 +1,248 @@
+use std::sync::Arc;
+
+use crate::alphabet::{Alphabet, Letter, MAX_LETTERS};
+use crate::game::ruleset::TileSet;
 use crate::game::util::BLANK_TILE_CHAR;
-use crate::loading::A_INDEX;
 
-const N_LETTERS: usize = 26;
-const N_TILES: usize = N_LETTERS + 1;
-const BLANK_TILE_INDEX: usize = 26;
+lazy_static! {
+    static ref ENGLISH: Arc<Alphabet> = Arc::new(Alphabet::english());
+}
 
-#[derive(Debug, Clone)]
+/// The tiles a player holds, counted by letter index in its alphabet. Tiles are written as in
+/// `Alphabet`, with `_` for a blank.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ScrabbleRack {
-    tile_counts: [u8; N_TILES],
+    alphabet: Arc<Alphabet>,
+    counts: [u8; MAX_LETTERS],
+    n_blanks: u8,
+    /// One bit per letter held, as in cross checks
+    held: u64,
+    len: usize,
 }
 
 impl ScrabbleRack {
+    /// A rack of English tiles
     pub fn new(tiles: &str) -> ScrabbleRack {
+        ScrabbleRack::with_shared_alphabet(tiles, ENGLISH.clone())
+    }
+
+    pub fn with_alphabet(tiles: &str, alphabet: &Alphabet) -> ScrabbleRack {
+        ScrabbleRack::with_shared_alphabet(tiles, Arc::new(alphabet.clone()))
+    }
+
+    /// A rack of the same alphabet holding `tiles`
+    pub fn with_tiles(&self, tiles: &str) -> ScrabbleRack {
+        ScrabbleRack::with_shared_alphabet(tiles, self.alphabet.clone())
+    }
+
+    fn with_shared_alphabet(tiles: &str, alphabet: Arc<Alphabet>) -> ScrabbleRack {
         let mut rack = ScrabbleRack {
-            tile_counts: [0; N_TILES],
+            alphabet,
+            counts: [0; MAX_LETTERS],
+            n_blanks: 0,
+            held: 0,
+            len: 0,
         };
         rack.add_tiles(tiles);
         rack
     }
 
-    pub fn add_tile(&mut self, tile: char) {
-        let index = ScrabbleRack::tile_index(tile);
-        *&mut self.tile_counts[index] += 1;
-    }
-
-    pub fn take_tile(&mut self, tile: char) -> Result<char, ()> {
-        let index = ScrabbleRack::tile_index(tile);
-        if self.tile_counts[index] > 0 {
-            *&mut self.tile_counts[index] -= 1;
-            Ok(tile)
-        } else if self.tile_counts[BLANK_TILE_INDEX] > 0 {
-            *&mut self.tile_counts[BLANK_TILE_INDEX] -= 1;
-            Ok(BLANK_TILE_CHAR)
-        } else {
-            Err(())
-        }
+    pub fn alphabet(&self) -> &Alphabet {
+        &self.alphabet
     }
 
-    fn tile_index(tile: char) -> usize {
-        (match tile {
-            tile if tile.is_ascii_lowercase() => tile as u8 - A_INDEX,
-            BLANK_TILE_CHAR => BLANK_TILE_INDEX as u8,
+    pub fn add_tile(&mut self, tile: char) {
+        match tile {
+            BLANK_TILE_CHAR => self.add_blank(),
+            tile if tile.is_lowercase() => match self.alphabet.letter(tile) {
+                Some(letter) => self.add_letter(letter),
+                None => panic!("Invalid rack tile: '{}' is not in the alphabet", tile),
+            },
             _ => panic!(
-                "Invalid rack tile: '{}'. Should be in 'a'-'z', or '_' for blank",
+                "Invalid rack tile: '{}'. Should be a lowercase letter, or '_' for blank",
                 tile
             ),
-        }) as usize
+        }
+    }
+
+    pub fn add_letter(&mut self, letter: Letter) {
+        self.counts[letter.index()] += 1;
+        self.held |= 1 << letter.index();
+        self.len += 1;
+    }
+
+    pub fn add_blank(&mut self) {
+        self.n_blanks += 1;
+        self.len += 1;
+    }
+
+    /// Removes `tile` from the rack, falling back to a blank if the letter itself isn't available.
+    /// Returns the tile that was actually taken, so it can be handed back with `add_tile`.
+    pub fn take_tile(&mut self, tile: char) -> Option<char> {
+        if self.remove_tile(tile) {
+            Some(tile)
+        } else if self.remove_blank() {
+            Some(BLANK_TILE_CHAR)
+        } else {
+            None
+        }
+    }
+
+    /// Removes exactly `tile` from the rack, without substituting a blank
+    pub fn remove_tile(&mut self, tile: char) -> bool {
+        match tile {
+            BLANK_TILE_CHAR => self.remove_blank(),
+            tile => self
+                .alphabet
+                .letter(tile)
+                .is_some_and(|letter| self.remove_letter(letter)),
+        }
+    }
+
+    /// Removes a tile of `letter`, without substituting a blank
+    pub fn remove_letter(&mut self, letter: Letter) -> bool {
+        let count = &mut self.counts[letter.index()];
+        if *count == 0 {
+            return false;
+        }
+        *count -= 1;
+        if *count == 0 {
+            self.held &= !(1 << letter.index());
+        }
+        self.len -= 1;
+        true
+    }
+
+    pub fn remove_blank(&mut self) -> bool {
+        if self.n_blanks == 0 {
+            return false;
+        }
+        self.n_blanks -= 1;
+        self.len -= 1;
+        true
+    }
+
+    /// The letters the rack can play, as a bitmask by letter index: every letter if it holds a
+    /// blank
+    pub fn playable_letters(&self) -> u64 {
+        if self.n_blanks > 0 {
+            self.alphabet.all_letters()
+        } else {
+            self.held
+        }
     }
 
-    fn add_tiles(&mut self, tiles: &str) {
+    pub fn add_tiles(&mut self, tiles: &str) {
         for tile in tiles.chars() {
             self.add_tile(tile);
         }
     }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The tiles on the rack in alphabet order, with blanks last
+    pub fn tiles(&self) -> String {
+        let mut tiles = String::with_capacity(self.len);
+        for letter in self.alphabet.letters() {
+            let symbol = self.alphabet.symbol(letter);
+            for _ in 0..self.counts[letter.index()] {
+                tiles.push(symbol);
+            }
+        }
+        for _ in 0..self.n_blanks {
+            tiles.push(BLANK_TILE_CHAR);
+        }
+        tiles
+    }
+
+    /// Every distinct non-empty group of tiles that could be exchanged, each written as in
+    /// `tiles`. Tiles with the same letter are interchangeable, so "aab" gives "a", "aa", "aab",
+    /// "ab" and "b" but not "a" twice.
+    pub fn exchanges(&self) -> Vec<String> {
+        let mut counts: Vec<(char, usize)> = Vec::new();
+        for tile in self.tiles().chars() {
+            match counts.last_mut() {
+                Some((last, count)) if *last == tile => *count += 1,
+                _ => counts.push((tile, 1)),
+            }
+        }
+        let mut exchanges = vec![String::new()];
+        for &(tile, count) in counts.iter() {
+            exchanges = exchanges
+                .iter()
+                .flat_map(|kept| {
+                    (0..=count).map(move |n| {
+                        let mut exchange = kept.clone();
+                        for _ in 0..n {
+                            exchange.push(tile);
+                        }
+                        exchange
+                    })
+                })
+                .collect();
+        }
+        // The first is the empty exchange, which is a pass
+        exchanges.remove(0);
+        exchanges.sort_unstable();
+        exchanges
+    }
+
+    /// The sum of the face values of the tiles on the rack, as used for end-of-game penalties
+    pub fn value(&self, tile_set: &TileSet) -> i32 {
+        self.tiles().chars().map(|tile| tile_set.value(tile)).sum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_exchanges() {
+        assert_eq!(
+            ScrabbleRack::new("aba").exchanges(),
+            vec!["a", "aa", "aab", "ab", "b"]
+        );
+        assert_eq!(ScrabbleRack::new("abcdefg").exchanges().len(), 127);
+        assert_eq!(ScrabbleRack::new("eeeeeee").exchanges().len(), 7);
+        let with_blanks = ScrabbleRack::new("_a_").exchanges();
+        assert_eq!(with_blanks, vec!["_", "__", "a", "a_", "a__"]);
+        assert!(ScrabbleRack::new("").exchanges().is_empty());
+    }
+
+    #[test]
+    fn test_letters() {
+        let spanish = Alphabet::spanish();
+        let ch = spanish.parse("ch").unwrap().chars().next().unwrap();
+        let enye = spanish.letter('ñ').unwrap();
+        let mut rack = ScrabbleRack::with_alphabet("ñz", &spanish);
+        rack.add_letter(spanish.letter(ch).unwrap());
+        assert_eq!(rack.len(), 3);
+        assert_eq!(rack.tiles(), spanish.parse("chñz").unwrap());
+        assert_eq!(rack.playable_letters().count_ones(), 3);
+        assert!(rack.remove_letter(enye));
+        assert!(!rack.remove_letter(enye));
+        assert_eq!(rack.playable_letters() & 1 << enye.index(), 0);
+        assert_eq!(rack.take_tile('ñ'), None);
+
+        rack.add_blank();
+        assert_eq!(rack.playable_letters(), spanish.all_letters());
+        assert_eq!(rack.take_tile('ñ'), Some(BLANK_TILE_CHAR));
+        assert_eq!(rack.with_tiles("ñ").alphabet(), &spanish);
+    }
+
+    #[test]
+    fn test_value() {
+        let english = TileSet::english();
+        assert_eq!(ScrabbleRack::new("qa_").value(&english), 11);
+        // Tiles outside the tile set's alphabet are worth nothing rather than a panic
+        let spanish = ScrabbleRack::with_alphabet("qñ", &Alphabet::spanish());
+        assert_eq!(spanish.value(&english), 10);
+    }
 }
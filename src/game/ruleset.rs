@@ -0,0 +1,238 @@
+use crate::alphabet::{Alphabet, AlphabetError};
+use crate::game::util::BLANK_TILE_CHAR;
+
+/// Letter, point value and number of tiles of the English Scrabble set
+const ENGLISH_TILES: [(&str, i32, usize); 26] = [
+    ("a", 1, 9),
+    ("b", 3, 2),
+    ("c", 3, 2),
+    ("d", 2, 4),
+    ("e", 1, 12),
+    ("f", 4, 2),
+    ("g", 2, 3),
+    ("h", 4, 2),
+    ("i", 1, 9),
+    ("j", 8, 1),
+    ("k", 5, 1),
+    ("l", 1, 4),
+    ("m", 3, 2),
+    ("n", 1, 6),
+    ("o", 1, 8),
+    ("p", 3, 2),
+    ("q", 10, 1),
+    ("r", 1, 6),
+    ("s", 1, 4),
+    ("t", 1, 6),
+    ("u", 1, 4),
+    ("v", 4, 2),
+    ("w", 4, 2),
+    ("x", 8, 1),
+    ("y", 4, 2),
+    ("z", 10, 1),
+];
+
+/// Letter, point value and number of tiles of a Words-With-Friends-style set
+const WWF_TILES: [(&str, i32, usize); 26] = [
+    ("a", 1, 9),
+    ("b", 4, 2),
+    ("c", 4, 2),
+    ("d", 2, 5),
+    ("e", 1, 13),
+    ("f", 4, 2),
+    ("g", 3, 3),
+    ("h", 3, 4),
+    ("i", 1, 8),
+    ("j", 10, 1),
+    ("k", 5, 1),
+    ("l", 2, 4),
+    ("m", 4, 2),
+    ("n", 2, 5),
+    ("o", 1, 8),
+    ("p", 4, 2),
+    ("q", 10, 1),
+    ("r", 1, 6),
+    ("s", 1, 5),
+    ("t", 1, 7),
+    ("u", 2, 4),
+    ("v", 5, 2),
+    ("w", 4, 2),
+    ("x", 8, 1),
+    ("y", 3, 2),
+    ("z", 10, 1),
+];
+
+/// Letter, point value and number of tiles of the Spanish Scrabble set
+const SPANISH_TILES: [(&str, i32, usize); 28] = [
+    ("a", 1, 12),
+    ("b", 3, 2),
+    ("c", 3, 4),
+    ("ch", 5, 1),
+    ("d", 2, 5),
+    ("e", 1, 12),
+    ("f", 4, 1),
+    ("g", 2, 2),
+    ("h", 4, 2),
+    ("i", 1, 6),
+    ("j", 8, 1),
+    ("l", 1, 4),
+    ("ll", 8, 1),
+    ("m", 3, 2),
+    ("n", 1, 5),
+    ("ñ", 8, 1),
+    ("o", 1, 9),
+    ("p", 3, 2),
+    ("q", 5, 1),
+    ("r", 1, 5),
+    ("rr", 8, 1),
+    ("s", 1, 6),
+    ("t", 1, 4),
+    ("u", 1, 5),
+    ("v", 4, 1),
+    ("x", 8, 1),
+    ("y", 4, 1),
+    ("z", 10, 1),
+];
+
+/// The letters of a game, with how much each is worth and how many of each are in the bag
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TileSet {
+    alphabet: Alphabet,
+    /// The value and count of each letter, by letter index
+    values: Vec<i32>,
+    counts: Vec<usize>,
+    blank_count: usize,
+}
+
+impl TileSet {
+    /// Builds a tile set from `(spelling, value, count)` triples, in alphabet order
+    pub fn new(tiles: &[(&str, i32, usize)], blank_count: usize) -> Result<TileSet, AlphabetError> {
+        let spellings: Vec<&str> = tiles.iter().map(|&(spelling, _, _)| spelling).collect();
+        Ok(TileSet {
+            alphabet: Alphabet::new(&spellings)?,
+            values: tiles.iter().map(|&(_, value, _)| value).collect(),
+            counts: tiles.iter().map(|&(_, _, count)| count).collect(),
+            blank_count,
+        })
+    }
+
+    pub fn english() -> TileSet {
+        TileSet::new(&ENGLISH_TILES, 2).unwrap()
+    }
+
+    pub fn wwf() -> TileSet {
+        TileSet::new(&WWF_TILES, 2).unwrap()
+    }
+
+    pub fn spanish() -> TileSet {
+        TileSet::new(&SPANISH_TILES, 2).unwrap()
+    }
+
+    pub fn alphabet(&self) -> &Alphabet {
+        &self.alphabet
+    }
+
+    pub fn blank_count(&self) -> usize {
+        self.blank_count
+    }
+
+    /// The number of tiles of `tile` in a full bag
+    pub fn count(&self, tile: char) -> usize {
+        match tile {
+            BLANK_TILE_CHAR => self.blank_count,
+            tile => self
+                .alphabet
+                .letter(tile)
+                .map_or(0, |letter| self.counts[letter.index()]),
+        }
+    }
+
+    /// The point value of a tile. Blanks, whether undesignated or recorded as an uppercase letter,
+    /// are worth nothing, as are tiles outside the alphabet.
+    pub fn value(&self, tile: char) -> i32 {
+        match tile {
+            BLANK_TILE_CHAR => 0,
+            tile if tile.is_uppercase() => 0,
+            tile => self
+                .alphabet
+                .letter(tile)
+                .map_or(0, |letter| self.values[letter.index()]),
+        }
+    }
+
+    /// Every tile in a full bag, blanks included
+    pub fn tiles(&self) -> String {
+        let mut tiles = String::new();
+        for letter in self.alphabet.letters() {
+            let symbol = self.alphabet.symbol(letter);
+            for _ in 0..self.counts[letter.index()] {
+                tiles.push(symbol);
+            }
+        }
+        for _ in 0..self.blank_count {
+            tiles.push(BLANK_TILE_CHAR);
+        }
+        tiles
+    }
+}
+
+/// The tiles of a game along with the rules that depend on them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ruleset {
+    pub tile_set: TileSet,
+    pub rack_size: usize,
+    /// The bonus for playing every tile on a full rack in one turn
+    pub bingo_bonus: i32,
+}
+
+impl Ruleset {
+    pub fn english() -> Ruleset {
+        Ruleset {
+            tile_set: TileSet::english(),
+            rack_size: 7,
+            bingo_bonus: 50,
+        }
+    }
+
+    pub fn wwf() -> Ruleset {
+        Ruleset {
+            tile_set: TileSet::wwf(),
+            rack_size: 7,
+            bingo_bonus: 35,
+        }
+    }
+}
+
+impl Default for Ruleset {
+    fn default() -> Ruleset {
+        Ruleset::english()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_presets() {
+        let english = TileSet::english();
+        assert_eq!(english.tiles().len(), 100);
+        assert_eq!(english.count('e'), 12);
+        assert_eq!(english.count(BLANK_TILE_CHAR), 2);
+        assert_eq!(english.value('q'), 10);
+        assert_eq!(english.value('Q'), 0);
+        assert_eq!(english.value('ñ'), 0);
+        assert_eq!(english.count('ñ'), 0);
+
+        let wwf = Ruleset::wwf();
+        assert_eq!(wwf.tile_set.tiles().len(), 104);
+        assert_eq!(wwf.tile_set.value('j'), 10);
+        assert_eq!(wwf.bingo_bonus, 35);
+
+        let spanish = TileSet::spanish();
+        assert_eq!(spanish.tiles().chars().count(), 100);
+        let rr = spanish.alphabet().parse("rr").unwrap();
+        assert_eq!(spanish.value(rr.chars().next().unwrap()), 8);
+        assert_eq!(spanish.value('ñ'), 8);
+        assert_eq!(spanish.count('ñ'), 1);
+    }
+}
@@ -1,8 +1,17 @@
-use crate::game::play_generation::GenerationAisle;
-use crate::game::util::{Position, BLANK_TILE_CHAR, EMPTY_SQUARE_CHAR};
+use std::fmt;
 
-pub fn score_play(aisle: &GenerationAisle, start_word_index: usize, word: &str) -> i32 {
-    let mut position = aisle.position(start_word_index);
+use crate::game::board::ScrabbleBoard;
+use crate::game::play_generation::{GenerationAisle, ScrabblePlay};
+use crate::game::ruleset::{Ruleset, TileSet};
+use crate::game::util::{Direction, Position, EMPTY_SQUARE_CHAR};
+
+pub fn score_play(
+    aisle: &GenerationAisle,
+    ruleset: &Ruleset,
+    start_word_index: usize,
+    word: &str,
+) -> i32 {
+    let tile_set = &ruleset.tile_set;
     let mut score = 0_i32;
     let mut new_word_score = 0;
     let mut new_word_multiplier = 1;
@@ -10,69 +19,161 @@ pub fn score_play(aisle: &GenerationAisle, start_word_index: usize, word: &str)
 
     for (i, ch) in word.chars().enumerate() {
         let square = &aisle.squares[start_word_index + i];
-        let score_modifier = match square.tile {
-            Some(_) => ScoreModifier::Plain,
+        let (tile, score_modifier) = match square.tile {
+            Some(tile) => (tile, ScoreModifier::Plain),
             None => {
                 tiles_used += 1;
-                ScoreModifier::at(position)
+                (ch, square.score_modifier)
             }
         };
         let word_multiplier = score_modifier.word_multiplier();
-        let ch_value = score_modifier.letter_multiplier() * letter_value(ch);
+        let ch_value = score_modifier.letter_multiplier() * tile_set.value(tile);
         new_word_score += ch_value;
         new_word_multiplier *= word_multiplier;
         if let Some(cross_checks) = &square.cross_checks {
             score += (cross_checks.cross_sum + ch_value) * word_multiplier;
         }
-        position = position.step(aisle.direction);
     }
     score += new_word_score * new_word_multiplier;
-    if tiles_used == 7 {
-        score += 50;
+    if tiles_used as usize == ruleset.rack_size {
+        score += ruleset.bingo_bonus;
     }
     score
 }
 
-pub fn letter_value(letter: char) -> i32 {
-    match letter {
-        BLANK_TILE_CHAR => 0,
-        'a' => 1,
-        'b' => 3,
-        'c' => 3,
-        'd' => 2,
-        'e' => 1,
-        'f' => 4,
-        'g' => 2,
-        'h' => 4,
-        'i' => 1,
-        'j' => 8,
-        'k' => 5,
-        'l' => 1,
-        'm' => 3,
-        'n' => 1,
-        'o' => 1,
-        'p' => 3,
-        'q' => 10,
-        'r' => 1,
-        's' => 1,
-        't' => 1,
-        'u' => 1,
-        'v' => 4,
-        'w' => 4,
-        'x' => 8,
-        'y' => 4,
-        'z' => 10,
-        _ => panic!("Unexpected letter to score: {}", letter),
+/// How a single word formed by a play contributes to its score
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordScore {
+    pub word: ScrabblePlay,
+    /// The value of each letter of the word, after letter multipliers
+    pub letter_values: Vec<i32>,
+    /// The premium squares covered by newly placed tiles of the word
+    pub premiums: Vec<(Position, ScoreModifier)>,
+    pub word_multiplier: i32,
+    pub score: i32,
+}
+
+/// The words formed by a play and what each one scores, with the main word first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayBreakdown {
+    pub words: Vec<WordScore>,
+    pub bingo_bonus: i32,
+}
+
+impl PlayBreakdown {
+    pub fn total(&self) -> i32 {
+        self.words.iter().map(|word| word.score).sum::<i32>() + self.bingo_bonus
+    }
+}
+
+impl fmt::Display for PlayBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for word_score in self.words.iter() {
+            let word = &word_score.word;
+            let direction = match word.direction {
+                Direction::Horizontal => "across",
+                Direction::Vertical => "down",
+            };
+            let values: Vec<String> = word_score
+                .letter_values
+                .iter()
+                .map(|value| value.to_string())
+                .collect();
+            write!(
+                f,
+                "{} at ({}, {}) {}: {}",
+                word.word,
+                word.start.row,
+                word.start.col,
+                direction,
+                values.join(" + ")
+            )?;
+            if word_score.word_multiplier != 1 {
+                write!(f, " x{}", word_score.word_multiplier)?;
+            }
+            writeln!(f, " = {}", word_score.score)?;
+        }
+        if self.bingo_bonus != 0 {
+            writeln!(f, "Bingo: {}", self.bingo_bonus)?;
+        }
+        write!(f, "Total: {}", self.total())
+    }
+}
+
+impl ScrabbleBoard {
+    /// Breaks down the score of a legal play before it is placed on this board. A single tile
+    /// should be given in the direction it forms a word, as `validate_play` reports it.
+    pub fn score_breakdown(&self, ruleset: &Ruleset, play: &ScrabblePlay) -> PlayBreakdown {
+        let tile_set = &ruleset.tile_set;
+        let mut words = vec![self.word_score(tile_set, play)];
+        let mut tiles_used = 0;
+        for (position, ch) in play.positions().zip(play.word.chars()) {
+            if self[position].is_none() {
+                tiles_used += 1;
+                if let Some(cross_word) = self.cross_word(position, play.direction, ch) {
+                    words.push(self.word_score(tile_set, &cross_word));
+                }
+            }
+        }
+        let bingo_bonus = if tiles_used == ruleset.rack_size {
+            ruleset.bingo_bonus
+        } else {
+            0
+        };
+        PlayBreakdown { words, bingo_bonus }
+    }
+
+    /// `play` spelt with the tiles already on the board wherever it covers them, so a blank
+    /// played earlier keeps its case
+    pub fn spelt_with_board_tiles(&self, play: &ScrabblePlay) -> ScrabblePlay {
+        let word = play
+            .positions()
+            .zip(play.word.chars())
+            .map(|(position, ch)| self[position].unwrap_or(ch))
+            .collect();
+        ScrabblePlay {
+            word,
+            ..play.clone()
+        }
+    }
+
+    fn word_score(&self, tile_set: &TileSet, word: &ScrabblePlay) -> WordScore {
+        let word = self.spelt_with_board_tiles(word);
+        let mut letter_values = Vec::with_capacity(word.word.len());
+        let mut premiums = Vec::new();
+        let mut word_multiplier = 1;
+        for (position, ch) in word.positions().zip(word.word.chars()) {
+            let score_modifier = match self[position] {
+                Some(_) => ScoreModifier::Plain,
+                None => self.layout.modifier(position),
+            };
+            if score_modifier != ScoreModifier::Plain {
+                premiums.push((position, score_modifier));
+            }
+            letter_values.push(score_modifier.letter_multiplier() * tile_set.value(ch));
+            word_multiplier *= score_modifier.word_multiplier();
+        }
+        let score = letter_values.iter().sum::<i32>() * word_multiplier;
+        WordScore {
+            word,
+            letter_values,
+            premiums,
+            word_multiplier,
+            score,
+        }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum ScoreModifier {
+    #[default]
     Plain,
     DoubleLetter,
     TripleLetter,
+    QuadrupleLetter,
     DoubleWord,
     TripleWord,
+    QuadrupleWord,
 }
 
 impl ScoreModifier {
@@ -80,6 +181,7 @@ impl ScoreModifier {
         match self {
             ScoreModifier::DoubleWord => 2,
             ScoreModifier::TripleWord => 3,
+            ScoreModifier::QuadrupleWord => 4,
             _ => 1,
         }
     }
@@ -88,6 +190,7 @@ impl ScoreModifier {
         match self {
             ScoreModifier::DoubleLetter => 2,
             ScoreModifier::TripleLetter => 3,
+            ScoreModifier::QuadrupleLetter => 4,
             _ => 1,
         }
     }
@@ -97,31 +200,99 @@ impl ScoreModifier {
             ScoreModifier::Plain => EMPTY_SQUARE_CHAR,
             ScoreModifier::DoubleLetter => '2',
             ScoreModifier::TripleLetter => '3',
+            ScoreModifier::QuadrupleLetter => '5',
             ScoreModifier::DoubleWord => '4',
             ScoreModifier::TripleWord => '6',
+            ScoreModifier::QuadrupleWord => '8',
         }
     }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::rack::ScrabbleRack;
+    use crate::game::PlayGenerator;
+    use crate::loading::load_dawg;
+
+    use super::*;
 
-    pub fn at(position: Position) -> ScoreModifier {
-        let (row_idx, col_idx) = (position.row as i32, position.col as i32);
-        match ((7 - row_idx).abs(), (7 - col_idx).abs()) {
-            (x, y) if x == y => match x {
-                1 => ScoreModifier::DoubleLetter,
-                2 => ScoreModifier::TripleLetter,
-                7 => ScoreModifier::TripleWord,
-                _ => ScoreModifier::DoubleWord,
-            },
-            (x, y) if x % 7 == 0 || y % 7 == 0 => match (x + y) % 7 {
-                4 => ScoreModifier::DoubleLetter,
-                7 => ScoreModifier::TripleWord,
-                _ => ScoreModifier::Plain,
-            },
-            (x, y) if (x - y).abs() == 4 => match (x + y) % 7 {
-                1 => ScoreModifier::TripleLetter,
-                6 => ScoreModifier::DoubleLetter,
-                _ => unreachable!(),
-            },
-            _ => ScoreModifier::Plain,
+    fn hello_board() -> ScrabbleBoard {
+        let mut board = ScrabbleBoard::default();
+        board.add_play(&ScrabblePlay {
+            start: Position { row: 7, col: 7 },
+            direction: Direction::Horizontal,
+            word: "hello".to_string(),
+        });
+        board
+    }
+
+    #[test]
+    fn test_breakdown() {
+        let play = ScrabblePlay {
+            start: Position { row: 6, col: 12 },
+            direction: Direction::Vertical,
+            word: "aS".to_string(),
+        };
+        let breakdown = hello_board().score_breakdown(&Ruleset::english(), &play);
+        assert_eq!(breakdown.words.len(), 2);
+        let main_word = &breakdown.words[0];
+        assert_eq!(main_word.word, play);
+        assert_eq!(main_word.letter_values, vec![2, 0]);
+        assert_eq!(
+            main_word.premiums,
+            vec![(Position { row: 6, col: 12 }, ScoreModifier::DoubleLetter)]
+        );
+        assert_eq!(main_word.score, 2);
+        let cross_word = &breakdown.words[1];
+        assert_eq!(cross_word.word.word, "helloS");
+        assert_eq!(cross_word.word.start, Position { row: 7, col: 7 });
+        assert_eq!(cross_word.letter_values, vec![4, 1, 1, 1, 1, 0]);
+        assert_eq!(cross_word.score, 8);
+        assert_eq!(breakdown.bingo_bonus, 0);
+        assert_eq!(breakdown.total(), 10);
+        assert_eq!(
+            breakdown.to_string(),
+            "aS at (6, 12) down: 2 + 0 = 2\nhelloS at (7, 7) across: 4 + 1 + 1 + 1 + 1 + 0 = 8\nTotal: 10"
+        );
+    }
+
+    #[test]
+    fn test_breakdown_blank_on_board() {
+        let mut board = ScrabbleBoard::default();
+        board.add_play(&ScrabblePlay {
+            start: Position { row: 7, col: 7 },
+            direction: Direction::Horizontal,
+            word: "hEllo".to_string(),
+        });
+        let play = ScrabblePlay {
+            start: Position { row: 7, col: 7 },
+            direction: Direction::Horizontal,
+            word: "hellos".to_string(),
+        };
+        let breakdown = board.score_breakdown(&Ruleset::english(), &play);
+        assert_eq!(breakdown.words[0].word.word, "hEllos");
+        assert_eq!(breakdown.words[0].letter_values, vec![4, 0, 1, 1, 1, 1]);
+        assert_eq!(breakdown.total(), 8);
+    }
+
+    #[test]
+    fn test_breakdown_matches_score_play() {
+        let dawg = load_dawg();
+        let ruleset = Ruleset::english();
+        let board = hello_board();
+        let generator = PlayGenerator {
+            dawg,
+            ruleset: &ruleset,
+            checked_board: board.to_checked_board(dawg, &ruleset.tile_set),
+            rack: ScrabbleRack::new("abcde_s"),
+        };
+        let plays = generator.plays();
+        assert!(plays
+            .iter()
+            .any(|scored| scored.score > ruleset.bingo_bonus));
+        for scored in plays {
+            let breakdown = board.score_breakdown(&ruleset, &scored.play);
+            assert_eq!(breakdown.total(), scored.score);
         }
     }
 }
@@ -0,0 +1,310 @@
+use std::time::{Duration, Instant};
+
+use crate::dawg::Dawg;
+use crate::game::bag::TileBag;
+use crate::game::board::ScrabbleBoard;
+use crate::game::evaluation::{rank_moves, Evaluator};
+use crate::game::play_generation::{PlayGenerator, ScoredMove};
+use crate::game::rack::ScrabbleRack;
+use crate::game::rng::Rng;
+use crate::game::ruleset::Ruleset;
+use crate::game::state::Move;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulationSettings {
+    /// How many of the moves ranked best by static equity are simulated
+    pub n_candidates: usize,
+    /// How many turns are played out after each candidate, alternating between the opponent and
+    /// the player
+    pub n_plies: usize,
+    /// How many sets of opponent racks and draws each candidate is played out with
+    pub iterations: usize,
+    /// Stops after the iteration running when this much time has passed. Only simulations
+    /// bounded by `iterations` alone are reproducible.
+    pub time_limit: Option<Duration>,
+    pub seed: u64,
+}
+
+impl Default for SimulationSettings {
+    fn default() -> SimulationSettings {
+        SimulationSettings {
+            n_candidates: 10,
+            n_plies: 2,
+            iterations: 100,
+            time_limit: None,
+            seed: 0,
+        }
+    }
+}
+
+/// A candidate move with the results of playing it out
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatedMove {
+    pub scored: ScoredMove,
+    /// The equity the evaluator gave the move before simulating
+    pub static_equity: f64,
+    /// The spread gained over the simulated turns, plus the value of the player's final leave
+    /// less that of the opponent's, averaged over the iterations
+    pub mean_equity: f64,
+    pub stddev_equity: f64,
+    /// How often the player ended the simulated turns ahead, counting ties as half
+    pub win_percentage: f64,
+    pub iterations: usize,
+}
+
+/// Judges moves by playing them out against sampled opponent racks and draws (a Monte Carlo
+/// simulation), with both sides making the move of best static equity in the turns that follow.
+///
+/// Every candidate is played out with the same racks and draws in each iteration, so the
+/// differences between them come from the moves rather than the luck of the draw.
+pub struct Simulator<'a, E: Evaluator + ?Sized> {
+    pub dawg: &'a Dawg<'a>,
+    pub ruleset: &'a Ruleset,
+    /// Picks the candidates and the moves played out, and values the final leaves
+    pub evaluator: &'a E,
+    pub settings: SimulationSettings,
+}
+
+/// What happened in one play-out of a candidate
+struct Outcome {
+    spread: i32,
+    equity: f64,
+}
+
+impl<'a, E: Evaluator + ?Sized> Simulator<'a, E> {
+    /// Simulates the best candidate moves for `rack` on `board` with `unseen` tiles in the bag
+    /// and on the opponent's rack, returning them best first by mean equity. `spread` is the
+    /// player's current lead, which decides who wins.
+    pub fn simulate(
+        &self,
+        board: &ScrabbleBoard,
+        rack: &ScrabbleRack,
+        unseen: &str,
+        spread: i32,
+    ) -> Vec<SimulatedMove> {
+        let bag_len = unseen
+            .chars()
+            .count()
+            .saturating_sub(self.ruleset.rack_size);
+        let generator = PlayGenerator {
+            dawg: self.dawg,
+            ruleset: self.ruleset,
+            checked_board: board.to_checked_board(self.dawg, &self.ruleset.tile_set),
+            rack: rack.clone(),
+        };
+        let mut candidates = rank_moves(self.evaluator, board, rack, generator.moves(bag_len));
+        candidates.truncate(self.settings.n_candidates);
+
+        let mut totals = vec![(0.0, 0.0, 0.0); candidates.len()];
+        let mut rng = Rng::new(self.settings.seed);
+        let start = Instant::now();
+        let mut iterations = 0;
+        while iterations < self.settings.iterations {
+            let bag = TileBag::new(unseen, rng.next_u64());
+            for (candidate, total) in candidates.iter().zip(totals.iter_mut()) {
+                let outcome = self.play_out(board, rack, bag.clone(), &candidate.scored);
+                total.0 += outcome.equity;
+                total.1 += outcome.equity * outcome.equity;
+                total.2 += match (spread + outcome.spread).signum() {
+                    1 => 1.0,
+                    0 => 0.5,
+                    _ => 0.0,
+                };
+            }
+            iterations += 1;
+            if let Some(time_limit) = self.settings.time_limit {
+                if start.elapsed() >= time_limit {
+                    break;
+                }
+            }
+        }
+
+        let n = iterations.max(1) as f64;
+        let mut simulated: Vec<SimulatedMove> = candidates
+            .into_iter()
+            .zip(totals)
+            .map(|(candidate, (sum, sum_of_squares, wins))| {
+                let mean = sum / n;
+                let variance = match iterations {
+                    0 | 1 => 0.0,
+                    _ => ((sum_of_squares - n * mean * mean) / (n - 1.0)).max(0.0),
+                };
+                SimulatedMove {
+                    scored: candidate.scored,
+                    static_equity: candidate.equity,
+                    mean_equity: mean,
+                    stddev_equity: variance.sqrt(),
+                    win_percentage: 100.0 * wins / n,
+                    iterations,
+                }
+            })
+            .collect();
+        simulated.sort_by(|a, b| b.mean_equity.total_cmp(&a.mean_equity));
+        simulated
+    }
+
+    fn play_out(
+        &self,
+        board: &ScrabbleBoard,
+        rack: &ScrabbleRack,
+        mut bag: TileBag,
+        candidate: &ScoredMove,
+    ) -> Outcome {
+        let mut board = board.clone();
+        let mut opponent = rack.with_tiles("");
+        self.refill(&mut opponent, &mut bag);
+        let mut player = candidate.leave(&board, rack);
+        match &candidate.played {
+            Move::Place(play) => {
+                board.add_play(play);
+                self.refill(&mut player, &mut bag);
+            }
+            Move::Exchange(tiles) => {
+                self.refill(&mut player, &mut bag);
+                for tile in tiles.chars() {
+                    bag.return_tile(tile);
+                }
+            }
+            Move::Pass => {}
+        }
+
+        // Racks and spread from the player's point of view. The first ply is the candidate's.
+        let mut racks = [player, opponent];
+        let mut spread = candidate.score;
+        let mut mover = 0;
+        for ply in 0..=self.settings.n_plies {
+            if ply > 0 {
+                let scored = self.best_move(&board, &racks[mover]);
+                let leave = scored.leave(&board, &racks[mover]);
+                if let Move::Place(play) = &scored.played {
+                    board.add_play(play);
+                }
+                racks[mover] = leave;
+                self.refill(&mut racks[mover], &mut bag);
+                spread += sign(mover) * scored.score;
+            }
+            if racks[mover].is_empty() {
+                let other = racks[1 - mover].value(&self.ruleset.tile_set);
+                spread += sign(mover) * 2 * other;
+                return Outcome {
+                    spread,
+                    equity: spread as f64,
+                };
+            }
+            mover = 1 - mover;
+        }
+        let equity = spread as f64 + self.evaluator.leave_value(&racks[0])
+            - self.evaluator.leave_value(&racks[1]);
+        Outcome { spread, equity }
+    }
+
+    /// The placement of best static equity, or a pass if there is none. Playing out exchanges
+    /// would make every ply far slower for little difference.
+    fn best_move(&self, board: &ScrabbleBoard, rack: &ScrabbleRack) -> ScoredMove {
+        let generator = PlayGenerator {
+            dawg: self.dawg,
+            ruleset: self.ruleset,
+            checked_board: board.to_checked_board(self.dawg, &self.ruleset.tile_set),
+            rack: rack.clone(),
+        };
+        let plays = generator
+            .plays()
+            .into_iter()
+            .map(ScoredMove::from)
+            .collect();
+        rank_moves(self.evaluator, board, rack, plays)
+            .into_iter()
+            .next()
+            .map(|ranked| ranked.scored)
+            .unwrap_or(ScoredMove {
+                played: Move::Pass,
+                score: 0,
+            })
+    }
+
+    fn refill(&self, rack: &mut ScrabbleRack, bag: &mut TileBag) {
+        while rack.len() < self.ruleset.rack_size {
+            match bag.draw() {
+                Some(tile) => rack.add_tile(tile),
+                None => break,
+            }
+        }
+    }
+}
+
+/// Scores by the player count towards the spread, and by the opponent against it
+fn sign(mover: usize) -> i32 {
+    if mover == 0 {
+        1
+    } else {
+        -1
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::evaluation::LeaveHeuristic;
+    use crate::game::state::Game;
+    use crate::loading::load_dawg;
+
+    #[test]
+    fn test_simulate() {
+        let ruleset = Ruleset::english();
+        let heuristic = LeaveHeuristic::default();
+        let simulator = |settings| Simulator {
+            dawg: load_dawg(),
+            ruleset: &ruleset,
+            evaluator: &heuristic,
+            settings,
+        };
+        let game = Game::new(load_dawg(), 2, 3);
+        let rack = &game.players()[0].rack;
+        let settings = SimulationSettings {
+            n_candidates: 3,
+            iterations: 4,
+            seed: 11,
+            ..SimulationSettings::default()
+        };
+        let simulated =
+            simulator(settings.clone()).simulate(game.board(), rack, &game.unseen_tiles(0), 0);
+        assert_eq!(simulated.len(), 3);
+        assert!(simulated
+            .windows(2)
+            .all(|pair| pair[0].mean_equity >= pair[1].mean_equity));
+        for candidate in simulated.iter() {
+            assert_eq!(candidate.iterations, 4);
+            assert!((0.0..=100.0).contains(&candidate.win_percentage));
+            assert!(candidate.stddev_equity >= 0.0);
+        }
+
+        // The same seed gives the same results
+        let again = simulator(settings).simulate(game.board(), rack, &game.unseen_tiles(0), 0);
+        assert_eq!(simulated, again);
+    }
+
+    #[test]
+    fn test_simulation_budget() {
+        let ruleset = Ruleset::english();
+        let heuristic = LeaveHeuristic::default();
+        let board = ScrabbleBoard::default();
+        let settings = SimulationSettings {
+            n_candidates: 2,
+            n_plies: 1,
+            iterations: 1000,
+            time_limit: Some(Duration::ZERO),
+            ..SimulationSettings::default()
+        };
+        let simulator = Simulator {
+            dawg: load_dawg(),
+            ruleset: &ruleset,
+            evaluator: &heuristic,
+            settings,
+        };
+        let unseen = "aeiourstlnaeiourstln";
+        let simulated = simulator.simulate(&board, &ScrabbleRack::new("qqvvwwx"), unseen, 0);
+        assert!(simulated.iter().all(|candidate| candidate.iterations == 1));
+        // With no words to play, exchanging beats passing
+        assert!(matches!(simulated[0].scored.played, Move::Exchange(_)));
+    }
+}
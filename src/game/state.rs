@@ -0,0 +1,446 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::dawg::Dawg;
+use crate::game::bag::TileBag;
+use crate::game::board::ScrabbleBoard;
+use crate::game::layout::BoardLayout;
+use crate::game::play_generation::{PlayGenerator, ScoredMove, ScrabblePlay};
+use crate::game::rack::ScrabbleRack;
+use crate::game::ruleset::Ruleset;
+use crate::game::util::rack_tile;
+use crate::game::validation::PlayError;
+
+/// The game ends once this many turns in a row have scored nothing
+pub(crate) const MAX_SCORELESS_TURNS: usize = 6;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Move {
+    Place(ScrabblePlay),
+    /// Returns the given rack tiles to the bag in exchange for the same number of new ones
+    Exchange(String),
+    Pass,
+}
+
+#[derive(Debug, Clone)]
+pub struct Player {
+    pub rack: ScrabbleRack,
+    pub score: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Turn {
+    pub player: usize,
+    pub played: Move,
+    pub score: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameError {
+    GameOver,
+    InvalidPlay(PlayError),
+    /// A tile to exchange is not on the rack
+    TileNotOnRack(char),
+    /// Exchanges are only allowed while the bag holds at least a full rack of tiles
+    BagTooSmall,
+    /// An exchange must return at least one tile; a turn that changes nothing is a pass
+    EmptyExchange,
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::GameOver => write!(f, "The game is already over"),
+            GameError::InvalidPlay(error) => write!(f, "Invalid play: {}", error),
+            GameError::TileNotOnRack(tile) => write!(f, "Tile '{}' is not on the rack", tile),
+            GameError::BagTooSmall => write!(f, "Not enough tiles in the bag to exchange"),
+            GameError::EmptyExchange => write!(f, "No tiles to exchange"),
+        }
+    }
+}
+
+impl Error for GameError {}
+
+/// The full state of a game in progress: the board, the bag, each player's rack and score,
+/// and the moves played so far.
+pub struct Game<'a> {
+    dawg: &'a Dawg<'a>,
+    ruleset: Ruleset,
+    board: ScrabbleBoard,
+    bag: TileBag,
+    players: Vec<Player>,
+    current_player: usize,
+    history: Vec<Turn>,
+    scoreless_turns: usize,
+    is_over: bool,
+}
+
+impl<'a> Game<'a> {
+    /// Starts an English Scrabble game on the standard board with a full bag shuffled by `seed`,
+    /// and deals each player a rack. Panics if there are no players.
+    pub fn new(dawg: &'a Dawg, n_players: usize, seed: u64) -> Game<'a> {
+        Game::with_rules(
+            dawg,
+            BoardLayout::standard(),
+            Ruleset::english(),
+            n_players,
+            seed,
+        )
+    }
+
+    /// Panics if there are no players
+    pub fn with_rules(
+        dawg: &'a Dawg,
+        layout: BoardLayout,
+        ruleset: Ruleset,
+        n_players: usize,
+        seed: u64,
+    ) -> Game<'a> {
+        assert!(n_players > 0, "A game needs at least one player");
+        let bag = TileBag::full(&ruleset.tile_set, seed);
+        let mut game = Game {
+            dawg,
+            ruleset,
+            board: ScrabbleBoard::new(layout),
+            bag,
+            players: Vec::with_capacity(n_players),
+            current_player: 0,
+            history: Vec::new(),
+            scoreless_turns: 0,
+            is_over: false,
+        };
+        let empty = ScrabbleRack::with_alphabet("", game.ruleset.tile_set.alphabet());
+        for _ in 0..n_players {
+            let mut rack = empty.clone();
+            game.refill(&mut rack);
+            game.players.push(Player { rack, score: 0 });
+        }
+        game
+    }
+
+    pub fn board(&self) -> &ScrabbleBoard {
+        &self.board
+    }
+
+    pub fn ruleset(&self) -> &Ruleset {
+        &self.ruleset
+    }
+
+    pub fn bag(&self) -> &TileBag {
+        &self.bag
+    }
+
+    pub fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    /// The index of the player whose turn it is
+    pub fn current_player(&self) -> usize {
+        self.current_player
+    }
+
+    /// The tiles `player` can't see: those in the bag and on the other players' racks
+    pub fn unseen_tiles(&self, player: usize) -> String {
+        let mut unseen: String = self.bag.tiles().iter().collect();
+        for (index, other) in self.players.iter().enumerate() {
+            if index != player {
+                unseen.push_str(&other.rack.tiles());
+            }
+        }
+        unseen
+    }
+
+    pub fn history(&self) -> &[Turn] {
+        &self.history
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.is_over
+    }
+
+    /// Every legal move for the current player, or none once the game is over
+    pub fn moves(&self) -> Vec<ScoredMove> {
+        if self.is_over {
+            return Vec::new();
+        }
+        let generator = PlayGenerator {
+            dawg: self.dawg,
+            ruleset: &self.ruleset,
+            checked_board: self
+                .board
+                .to_checked_board(self.dawg, &self.ruleset.tile_set),
+            rack: self.players[self.current_player].rack.clone(),
+        };
+        generator.moves(self.bag.len())
+    }
+
+    /// Plays `played` for the current player and returns the score it earned
+    pub fn play(&mut self, played: Move) -> Result<i32, GameError> {
+        if self.is_over {
+            return Err(GameError::GameOver);
+        }
+        let score = match &played {
+            Move::Place(play) => self.place(play)?,
+            Move::Exchange(tiles) => self.exchange(tiles)?,
+            Move::Pass => 0,
+        };
+        let player = self.current_player;
+        self.players[player].score += score;
+        self.history.push(Turn {
+            player,
+            played,
+            score,
+        });
+
+        self.scoreless_turns = if score == 0 {
+            self.scoreless_turns + 1
+        } else {
+            0
+        };
+        if self.bag.is_empty() && self.players[player].rack.is_empty() {
+            self.finish(Some(player));
+        } else if self.scoreless_turns >= MAX_SCORELESS_TURNS {
+            self.finish(None);
+        } else {
+            self.current_player = (player + 1) % self.players.len();
+        }
+        Ok(score)
+    }
+
+    fn place(&mut self, play: &ScrabblePlay) -> Result<i32, GameError> {
+        let mut rack = self.players[self.current_player].rack.clone();
+        let validated = self
+            .board
+            .validate_play(self.dawg, &self.ruleset, &rack, play)
+            .map_err(GameError::InvalidPlay)?;
+        // A single tile may have been validated in the other direction
+        let main_word = &validated.words[0];
+        for (position, ch) in main_word.positions().zip(main_word.word.chars()) {
+            if self.board[position].is_none() {
+                rack.remove_tile(rack_tile(ch));
+            }
+        }
+        self.board.add_play(main_word);
+        self.refill(&mut rack);
+        self.players[self.current_player].rack = rack;
+        Ok(validated.score)
+    }
+
+    fn exchange(&mut self, tiles: &str) -> Result<i32, GameError> {
+        if tiles.is_empty() {
+            return Err(GameError::EmptyExchange);
+        }
+        if self.bag.len() < self.ruleset.rack_size {
+            return Err(GameError::BagTooSmall);
+        }
+        let mut rack = self.players[self.current_player].rack.clone();
+        for tile in tiles.chars() {
+            if !rack.remove_tile(tile) {
+                return Err(GameError::TileNotOnRack(tile));
+            }
+        }
+        // New tiles are drawn before the old ones go back, so a player can't redraw their own tiles
+        self.refill(&mut rack);
+        for tile in tiles.chars() {
+            self.bag.return_tile(tile);
+        }
+        self.players[self.current_player].rack = rack;
+        Ok(0)
+    }
+
+    fn refill(&mut self, rack: &mut ScrabbleRack) {
+        while rack.len() < self.ruleset.rack_size {
+            match self.bag.draw() {
+                Some(tile) => rack.add_tile(tile),
+                None => break,
+            }
+        }
+    }
+
+    /// Applies the end-of-game rack adjustments: every player loses the value of their remaining
+    /// tiles, and a player who went out gains the total of everyone else's.
+    fn finish(&mut self, went_out: Option<usize>) {
+        let mut total_remaining = 0;
+        for player in self.players.iter_mut() {
+            let remaining = player.rack.value(&self.ruleset.tile_set);
+            player.score -= remaining;
+            total_remaining += remaining;
+        }
+        if let Some(player) = went_out {
+            self.players[player].score += total_remaining;
+        }
+        self.is_over = true;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::util::{Direction, Position, BLANK_TILE_CHAR};
+    use crate::loading::load_dawg;
+
+    use super::*;
+
+    const RACK: usize = 7;
+
+    fn hello() -> ScrabblePlay {
+        ScrabblePlay {
+            start: Position { row: 7, col: 7 },
+            direction: Direction::Horizontal,
+            word: "hello".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_new_game() {
+        let game = Game::new(load_dawg(), 2, 0);
+        assert_eq!(game.players().len(), 2);
+        for player in game.players() {
+            assert_eq!(player.rack.len(), RACK);
+            assert_eq!(player.score, 0);
+        }
+        assert_eq!(game.bag().len(), 100 - 2 * RACK);
+        assert_eq!(game.current_player(), 0);
+        assert_eq!(game.unseen_tiles(0).len(), 100 - RACK);
+    }
+
+    #[test]
+    fn test_place() {
+        let mut game = Game::new(load_dawg(), 2, 0);
+        game.players[0].rack = ScrabbleRack::new("hel_oqq");
+        let play = ScrabblePlay {
+            word: "helLo".to_string(),
+            ..hello()
+        };
+        assert_eq!(game.play(Move::Place(play)), Ok(16));
+        assert_eq!(game.board()[Position { row: 7, col: 10 }], Some('L'));
+        assert_eq!(game.players()[0].score, 16);
+        assert_eq!(game.players()[0].rack.len(), RACK);
+        assert_eq!(game.bag().len(), 100 - 2 * RACK - 5);
+        assert_eq!(game.current_player(), 1);
+        assert_eq!(game.history().len(), 1);
+    }
+
+    #[test]
+    fn test_place_errors() {
+        let mut game = Game::new(load_dawg(), 2, 0);
+        game.players[0].rack = ScrabbleRack::new("helloqq");
+        let off_board = ScrabblePlay {
+            start: Position { row: 7, col: 12 },
+            ..hello()
+        };
+        assert_eq!(
+            game.play(Move::Place(off_board)),
+            Err(GameError::InvalidPlay(PlayError::OutOfBounds))
+        );
+        let blank = ScrabblePlay {
+            word: "hellO".to_string(),
+            ..hello()
+        };
+        assert_eq!(
+            game.play(Move::Place(blank)),
+            Err(GameError::InvalidPlay(PlayError::TileNotOnRack(
+                BLANK_TILE_CHAR
+            )))
+        );
+        assert!(game.play(Move::Place(hello())).is_ok());
+
+        game.players[1].rack = ScrabbleRack::new("abcdefg");
+        let conflicting = ScrabblePlay {
+            word: "jello".to_string(),
+            ..hello()
+        };
+        assert_eq!(
+            game.play(Move::Place(conflicting)),
+            Err(GameError::InvalidPlay(PlayError::ConflictingTile(
+                Position { row: 7, col: 7 }
+            )))
+        );
+        assert_eq!(
+            game.play(Move::Place(hello())),
+            Err(GameError::InvalidPlay(PlayError::NoTilesPlaced))
+        );
+    }
+
+    #[test]
+    fn test_exchange() {
+        let mut game = Game::new(load_dawg(), 2, 0);
+        game.players[0].rack = ScrabbleRack::new("qqqqaaa");
+        assert_eq!(game.play(Move::Exchange("qqqq".to_string())), Ok(0));
+        assert_eq!(game.players()[0].rack.len(), RACK);
+        assert_eq!(game.bag().len(), 100 - 2 * RACK);
+        assert_eq!(
+            game.play(Move::Exchange("z".to_string())),
+            Err(GameError::TileNotOnRack('z'))
+        );
+        assert_eq!(
+            game.play(Move::Exchange(String::new())),
+            Err(GameError::EmptyExchange)
+        );
+        assert_eq!(game.history().len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one player")]
+    fn test_no_players() {
+        Game::new(load_dawg(), 0, 0);
+    }
+
+    #[test]
+    fn test_moves() {
+        let mut game = Game::new(load_dawg(), 2, 0);
+        game.players[0].rack = ScrabbleRack::new("qqqqaaa");
+        let moves = game.moves();
+        let exchanges = moves
+            .iter()
+            .filter(|scored| matches!(scored.played, Move::Exchange(_)))
+            .count();
+        assert_eq!(exchanges, 5 * 4 - 1);
+        assert_eq!(moves.last().unwrap().played, Move::Pass);
+        let best = moves
+            .iter()
+            .max_by_key(|scored| scored.score)
+            .unwrap()
+            .clone();
+        assert!(matches!(best.played, Move::Place(_)));
+        assert_eq!(game.play(best.played), Ok(best.score));
+
+        while game.bag.len() >= RACK {
+            game.bag.draw();
+        }
+        let moves = game.moves();
+        assert!(!moves
+            .iter()
+            .any(|scored| matches!(scored.played, Move::Exchange(_))));
+        assert!(moves.contains(&ScoredMove {
+            played: Move::Pass,
+            score: 0
+        }));
+    }
+
+    #[test]
+    fn test_scoreless_turns_end_game() {
+        let mut game = Game::new(load_dawg(), 2, 0);
+        game.players[0].rack = ScrabbleRack::new("qz");
+        game.players[1].rack = ScrabbleRack::new("a_");
+        for _ in 0..MAX_SCORELESS_TURNS {
+            assert!(!game.is_over());
+            assert_eq!(game.play(Move::Pass), Ok(0));
+        }
+        assert!(game.is_over());
+        assert_eq!(game.players()[0].score, -20);
+        assert_eq!(game.players()[1].score, -1);
+        assert_eq!(game.play(Move::Pass), Err(GameError::GameOver));
+    }
+
+    #[test]
+    fn test_going_out_ends_game() {
+        let mut game = Game::new(load_dawg(), 2, 0);
+        while game.bag.draw().is_some() {}
+        game.players[0].rack = ScrabbleRack::new("hello");
+        game.players[1].rack = ScrabbleRack::new("qa");
+        assert_eq!(game.play(Move::Place(hello())), Ok(18));
+        assert!(game.is_over());
+        assert_eq!(game.players()[0].score, 18 + 11);
+        assert_eq!(game.players()[1].score, -11);
+    }
+}
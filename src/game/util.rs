@@ -1,8 +1,34 @@
 use std::slice::Iter;
 
-pub const BLANK_TILE_CHAR: char = '_';
+pub use crate::alphabet::BLANK_TILE_CHAR;
+use crate::alphabet::{to_lowercase, to_uppercase};
+
 pub const EMPTY_SQUARE_CHAR: char = ' ';
 
+/// The character recorded on the board for `letter` when it is played using `tile` from the rack.
+/// Blanks are recorded as the uppercase form of the letter they stand for.
+pub fn placed_tile(tile: char, letter: char) -> char {
+    if tile == BLANK_TILE_CHAR {
+        to_uppercase(letter)
+    } else {
+        letter
+    }
+}
+
+/// The rack tile a board tile was played from
+pub fn rack_tile(tile: char) -> char {
+    if tile.is_uppercase() {
+        BLANK_TILE_CHAR
+    } else {
+        tile
+    }
+}
+
+/// The letter a board tile stands for, whether or not it was played from a blank
+pub fn tile_letter(tile: char) -> char {
+    to_lowercase(tile)
+}
+
 #[derive(Debug, Clone, Copy, PartialOrd, PartialEq, Ord, Eq)]
 pub enum Direction {
     Horizontal,
@@ -10,6 +36,13 @@ pub enum Direction {
 }
 
 impl Direction {
+    pub fn perpendicular(&self) -> Direction {
+        match self {
+            Direction::Horizontal => Direction::Vertical,
+            Direction::Vertical => Direction::Horizontal,
+        }
+    }
+
     pub fn iterator() -> Iter<'static, Direction> {
         static DIRECTIONS: [Direction; 2] = [Direction::Horizontal, Direction::Vertical];
         DIRECTIONS.iter()
@@ -36,6 +69,13 @@ impl Position {
         }
     }
 
+    pub fn aisle(&self, direction: Direction) -> usize {
+        match direction {
+            Direction::Horizontal => self.row,
+            Direction::Vertical => self.col,
+        }
+    }
+
     pub fn cross(&self, direction: Direction) -> usize {
         match direction {
             Direction::Horizontal => self.col,
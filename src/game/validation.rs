@@ -0,0 +1,334 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::dawg::Dawg;
+use crate::game::board::ScrabbleBoard;
+use crate::game::play_generation::ScrabblePlay;
+use crate::game::rack::ScrabbleRack;
+use crate::game::ruleset::Ruleset;
+use crate::game::util::{rack_tile, tile_letter, Position, EMPTY_SQUARE_CHAR};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlayError {
+    OutOfBounds,
+    /// The word leaves this square empty
+    Gap(Position),
+    /// The play spells a different letter than the tile already at this position
+    ConflictingTile(Position),
+    NoTilesPlaced,
+    /// The word stops short of a tile adjoining one of its ends, so isn't the whole word formed
+    IncompleteWord,
+    TileNotOnRack(char),
+    NotConnected,
+    /// The first play of the game must cover the start square
+    NotOnStartSquare,
+    InvalidWord(String),
+    InvalidCrossWords(Vec<String>),
+}
+
+impl fmt::Display for PlayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlayError::OutOfBounds => write!(f, "The play extends off the board"),
+            PlayError::Gap(position) => write!(f, "The play leaves a gap at {:?}", position),
+            PlayError::ConflictingTile(position) => {
+                write!(f, "The play conflicts with the tile at {:?}", position)
+            }
+            PlayError::NoTilesPlaced => write!(f, "The play doesn't place any tiles"),
+            PlayError::IncompleteWord => {
+                write!(f, "The play doesn't include all the adjoining tiles")
+            }
+            PlayError::TileNotOnRack(tile) => write!(f, "Tile '{}' is not on the rack", tile),
+            PlayError::NotConnected => write!(f, "The play isn't connected to any tiles"),
+            PlayError::NotOnStartSquare => write!(f, "The first play must cover the start square"),
+            PlayError::InvalidWord(word) => write!(f, "'{}' is not a valid word", word),
+            PlayError::InvalidCrossWords(words) => {
+                write!(f, "Invalid cross words: {}", words.join(", "))
+            }
+        }
+    }
+}
+
+impl Error for PlayError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatedPlay {
+    pub score: i32,
+    /// Every word formed by the play, including tiles already on the board; the main word first
+    pub words: Vec<ScrabblePlay>,
+}
+
+impl ScrabbleBoard {
+    /// Checks that `play` is a legal move for `rack` on this board, returning its score and the
+    /// words it forms
+    pub fn validate_play(
+        &self,
+        dawg: &Dawg,
+        ruleset: &Ruleset,
+        rack: &ScrabbleRack,
+        play: &ScrabblePlay,
+    ) -> Result<ValidatedPlay, PlayError> {
+        if let Some(transposed) = self.single_tile_cross_play(play) {
+            return self.validate_play(dawg, ruleset, rack, &transposed);
+        }
+        let placed = self.placed_tiles(play)?;
+        if self.extends_past_word(play) {
+            return Err(PlayError::IncompleteWord);
+        }
+
+        let mut rack = rack.clone();
+        for &(_, ch) in placed.iter() {
+            if !rack.remove_tile(rack_tile(ch)) {
+                return Err(PlayError::TileNotOnRack(rack_tile(ch)));
+            }
+        }
+
+        let cross_words: Vec<ScrabblePlay> = placed
+            .iter()
+            .filter_map(|&(position, ch)| self.cross_word(position, play.direction, ch))
+            .collect();
+        if self.is_empty() {
+            if !play
+                .positions()
+                .any(|position| position == self.layout.start())
+            {
+                return Err(PlayError::NotOnStartSquare);
+            }
+        } else if placed.len() == play.word.chars().count() && cross_words.is_empty() {
+            return Err(PlayError::NotConnected);
+        }
+
+        if !dawg.contains(&play.word) {
+            return Err(PlayError::InvalidWord(play.word.clone()));
+        }
+        let invalid_cross_words: Vec<String> = cross_words
+            .iter()
+            .filter(|cross_word| !dawg.contains(&cross_word.word))
+            .map(|cross_word| cross_word.word.clone())
+            .collect();
+        if !invalid_cross_words.is_empty() {
+            return Err(PlayError::InvalidCrossWords(invalid_cross_words));
+        }
+
+        let play = self.spelt_with_board_tiles(play);
+        let checked_board = self.to_checked_board(dawg, &ruleset.tile_set);
+        let score = play.score(&checked_board, ruleset);
+        let mut words = vec![play];
+        words.extend(cross_words);
+        Ok(ValidatedPlay { score, words })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.squares.iter().all(Option::is_none)
+    }
+
+    /// A single tile only forms a word in the direction it has neighbors, which may not be the
+    /// direction it was submitted with, so it is replaced by the whole word through it. The
+    /// submitted direction is preferred when the tile has neighbors both ways.
+    fn single_tile_cross_play(&self, play: &ScrabblePlay) -> Option<ScrabblePlay> {
+        let mut chars = play.word.chars();
+        let ch = chars.next()?;
+        if chars.next().is_some() || !self.layout.contains(play.start) || self[play.start].is_some()
+        {
+            return None;
+        }
+        self.cross_word(play.start, play.direction.perpendicular(), ch)
+            .or_else(|| self.cross_word(play.start, play.direction, ch))
+    }
+
+    /// The positions and tiles of the letters `play` adds to the board
+    fn placed_tiles(&self, play: &ScrabblePlay) -> Result<Vec<(Position, char)>, PlayError> {
+        let length = play.word.chars().count();
+        let start = play.start;
+        if length == 0
+            || !self.layout.contains(start)
+            || start.cross(play.direction) + length > self.layout.aisle_len(play.direction)
+        {
+            return Err(PlayError::OutOfBounds);
+        }
+
+        let mut placed = Vec::with_capacity(length);
+        for (position, ch) in play.positions().zip(play.word.chars()) {
+            match self[position] {
+                _ if ch == EMPTY_SQUARE_CHAR => return Err(PlayError::Gap(position)),
+                Some(tile) if tile_letter(tile) != tile_letter(ch) => {
+                    return Err(PlayError::ConflictingTile(position));
+                }
+                Some(_) => {}
+                None => placed.push((position, ch)),
+            }
+        }
+        if placed.is_empty() {
+            return Err(PlayError::NoTilesPlaced);
+        }
+        Ok(placed)
+    }
+
+    /// Whether a tile directly adjoins either end of the word, so should have been part of it
+    fn extends_past_word(&self, play: &ScrabblePlay) -> bool {
+        let direction = play.direction;
+        let aisle = play.start.aisle(direction);
+        let first = play.start.cross(direction);
+        let after = first + play.word.chars().count();
+        let occupied = |cross| self[Position::from_aisle_cross(direction, aisle, cross)].is_some();
+        (first > 0 && occupied(first - 1))
+            || (after < self.layout.aisle_len(direction) && occupied(after))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::util::Direction;
+    use crate::loading::load_dawg;
+
+    use super::*;
+
+    fn hello_board() -> ScrabbleBoard {
+        let mut board = ScrabbleBoard::default();
+        board.add_play(&play(7, 7, Direction::Horizontal, "hello"));
+        board
+    }
+
+    fn play(row: usize, col: usize, direction: Direction, word: &str) -> ScrabblePlay {
+        ScrabblePlay {
+            start: Position { row, col },
+            direction,
+            word: word.to_string(),
+        }
+    }
+
+    fn validate(board: &ScrabbleBoard, rack: &str, play: ScrabblePlay) -> Result<i32, PlayError> {
+        let rack = ScrabbleRack::new(rack);
+        board
+            .validate_play(load_dawg(), &Ruleset::english(), &rack, &play)
+            .map(|validated| validated.score)
+    }
+
+    #[test]
+    fn test_valid_plays() {
+        let board = hello_board();
+        let rack = ScrabbleRack::new("as_");
+
+        let extension = play(7, 7, Direction::Horizontal, "hellos");
+        let validated = board
+            .validate_play(load_dawg(), &Ruleset::english(), &rack, &extension)
+            .unwrap();
+        assert_eq!(validated.score, 9);
+        assert_eq!(validated.words, vec![extension.clone()]);
+
+        let blank_extension = play(7, 7, Direction::Horizontal, "helloS");
+        assert_eq!(validate(&board, "_", blank_extension), Ok(8));
+
+        let parallel = play(6, 12, Direction::Vertical, "as");
+        let validated = board
+            .validate_play(load_dawg(), &Ruleset::english(), &rack, &parallel)
+            .unwrap();
+        assert_eq!(validated.score, 12);
+        assert_eq!(validated.words, vec![parallel, extension.clone()]);
+    }
+
+    #[test]
+    fn test_single_tile() {
+        let board = hello_board();
+        let single = play(7, 12, Direction::Vertical, "s");
+        let validated = board
+            .validate_play(
+                load_dawg(),
+                &Ruleset::english(),
+                &ScrabbleRack::new("s"),
+                &single,
+            )
+            .unwrap();
+        assert_eq!(validated.score, 9);
+        assert_eq!(
+            validated.words,
+            vec![play(7, 7, Direction::Horizontal, "hellos")]
+        );
+
+        let across = play(7, 12, Direction::Horizontal, "s");
+        let validated = board
+            .validate_play(
+                load_dawg(),
+                &Ruleset::english(),
+                &ScrabbleRack::new("s"),
+                &across,
+            )
+            .unwrap();
+        assert_eq!(validated.score, 9);
+        assert_eq!(
+            validated.words,
+            vec![play(7, 7, Direction::Horizontal, "hellos")]
+        );
+    }
+
+    #[test]
+    fn test_blank_on_board() {
+        let mut board = ScrabbleBoard::default();
+        board.add_play(&play(7, 7, Direction::Horizontal, "hEllo"));
+        let extension = play(7, 7, Direction::Horizontal, "hellos");
+        let validated = board
+            .validate_play(
+                load_dawg(),
+                &Ruleset::english(),
+                &ScrabbleRack::new("s"),
+                &extension,
+            )
+            .unwrap();
+        assert_eq!(validated.score, 8);
+        assert_eq!(
+            validated.words,
+            vec![play(7, 7, Direction::Horizontal, "hEllos")]
+        );
+    }
+
+    #[test]
+    fn test_placement_errors() {
+        let board = hello_board();
+        let h = Direction::Horizontal;
+        assert_eq!(
+            validate(&board, "s", play(7, 12, h, "hellos")),
+            Err(PlayError::OutOfBounds)
+        );
+        assert_eq!(
+            validate(&board, "ct", play(0, 0, h, "c t")),
+            Err(PlayError::Gap(Position { row: 0, col: 1 }))
+        );
+        assert_eq!(
+            validate(&board, "j", play(7, 7, h, "jello")),
+            Err(PlayError::ConflictingTile(Position { row: 7, col: 7 }))
+        );
+        assert_eq!(
+            validate(&board, "s", play(7, 11, h, "os")),
+            Err(PlayError::IncompleteWord)
+        );
+        assert_eq!(
+            validate(&board, "a", play(7, 7, h, "hellos")),
+            Err(PlayError::TileNotOnRack('s'))
+        );
+        assert_eq!(
+            validate(&board, "cat", play(0, 0, h, "cat")),
+            Err(PlayError::NotConnected)
+        );
+        assert_eq!(
+            validate(&ScrabbleBoard::default(), "cat", play(0, 0, h, "cat")),
+            Err(PlayError::NotOnStartSquare)
+        );
+        assert_eq!(
+            validate(&ScrabbleBoard::default(), "cat", play(7, 5, h, "cat")),
+            Ok(10)
+        );
+    }
+
+    #[test]
+    fn test_word_errors() {
+        let board = hello_board();
+        assert_eq!(
+            validate(&board, "x", play(7, 7, Direction::Horizontal, "hellox")),
+            Err(PlayError::InvalidWord("hellox".to_string()))
+        );
+        assert_eq!(
+            validate(&board, "ax", play(6, 12, Direction::Vertical, "ax")),
+            Err(PlayError::InvalidCrossWords(vec!["hellox".to_string()]))
+        );
+    }
+}
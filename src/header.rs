@@ -0,0 +1,143 @@
+use std::convert::TryInto;
+use std::io::{self, Write};
+
+use crate::alphabet::Alphabet;
+use crate::dawg::DawgError;
+
+/// The first bytes of every dawg file
+pub const DAWG_MAGIC: &[u8; 8] = b"SCRBDAWG";
+/// The version of the file layout written by `Dawg::write_to`
+pub const DAWG_FORMAT_VERSION: u16 = 2;
+
+/// Details of the lexicon a dawg was built from, kept in its file header
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DawgMetadata {
+    /// The name of the word list, e.g. "CSW21"
+    pub lexicon: String,
+    /// When the word list was published, in whatever format its publisher uses
+    pub date: String,
+    pub word_count: usize,
+}
+
+/// The header of a dawg file, which is followed by the packed edges and their letter masks, as
+/// described on `Dawg`. All numbers are little-endian, and strings are UTF-8 preceded by their
+/// length in bytes:
+///
+/// - the magic bytes `SCRBDAWG`
+/// - the format version, a `u16`
+/// - the number of letters in the alphabet, a `u8`, then the spelling of each with a `u8` length
+/// - the lexicon name and date, each with a `u16` length
+/// - the word count, edge count and FNV-1a checksum of the edges and masks, each a `u32`
+pub(crate) struct DawgHeader {
+    pub alphabet: Alphabet,
+    pub metadata: DawgMetadata,
+    pub n_edges: usize,
+    pub checksum: u32,
+    /// The length of the header in bytes, i.e. where the edges start
+    pub len: usize,
+}
+
+impl DawgHeader {
+    pub fn read(bytes: &[u8]) -> Result<DawgHeader, DawgError> {
+        let mut reader = HeaderReader { bytes, position: 0 };
+        if bytes.len() < DAWG_MAGIC.len() || &bytes[..DAWG_MAGIC.len()] != DAWG_MAGIC {
+            return Err(DawgError::NotADawg);
+        }
+        reader.take(DAWG_MAGIC.len())?;
+        let version = reader.u16()?;
+        if version != DAWG_FORMAT_VERSION {
+            return Err(DawgError::UnsupportedVersion(version));
+        }
+        let n_letters = reader.u8()?;
+        let mut spellings = Vec::with_capacity(n_letters as usize);
+        for _ in 0..n_letters {
+            let len = reader.u8()? as usize;
+            spellings.push(reader.string(len)?);
+        }
+        let spellings: Vec<&str> = spellings.iter().map(String::as_str).collect();
+        let alphabet = Alphabet::new(&spellings).map_err(DawgError::InvalidAlphabet)?;
+        let len = reader.u16()? as usize;
+        let lexicon = reader.string(len)?;
+        let len = reader.u16()? as usize;
+        let date = reader.string(len)?;
+        let word_count = reader.u32()? as usize;
+        let n_edges = reader.u32()? as usize;
+        let checksum = reader.u32()?;
+        Ok(DawgHeader {
+            alphabet,
+            metadata: DawgMetadata {
+                lexicon,
+                date,
+                word_count,
+            },
+            n_edges,
+            checksum,
+            len: reader.position,
+        })
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(DAWG_MAGIC)?;
+        writer.write_all(&DAWG_FORMAT_VERSION.to_le_bytes())?;
+        writer.write_all(&[self.alphabet.len() as u8])?;
+        for letter in self.alphabet.letters() {
+            let spelling = self.alphabet.spelling(letter);
+            writer.write_all(&[spelling.len() as u8])?;
+            writer.write_all(spelling.as_bytes())?;
+        }
+        for text in [&self.metadata.lexicon, &self.metadata.date] {
+            let len: u16 = text
+                .len()
+                .try_into()
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Metadata too long"))?;
+            writer.write_all(&len.to_le_bytes())?;
+            writer.write_all(text.as_bytes())?;
+        }
+        writer.write_all(&(self.metadata.word_count as u32).to_le_bytes())?;
+        writer.write_all(&(self.n_edges as u32).to_le_bytes())?;
+        writer.write_all(&self.checksum.to_le_bytes())
+    }
+}
+
+/// The 32-bit FNV-1a hash of `bytes`
+pub(crate) fn checksum(bytes: &[u8]) -> u32 {
+    let mut hash: u32 = 0x811c_9dc5;
+    for &byte in bytes {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    hash
+}
+
+struct HeaderReader<'b> {
+    bytes: &'b [u8],
+    position: usize,
+}
+
+impl<'b> HeaderReader<'b> {
+    fn take(&mut self, len: usize) -> Result<&'b [u8], DawgError> {
+        let end = self.position + len;
+        if end > self.bytes.len() {
+            return Err(DawgError::Truncated);
+        }
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, DawgError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, DawgError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, DawgError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn string(&mut self, len: usize) -> Result<String, DawgError> {
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| DawgError::InvalidHeader)
+    }
+}
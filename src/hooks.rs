@@ -0,0 +1,56 @@
+use crate::dawg::Dawg;
+use crate::game::CrossChecks;
+
+/// The single letters that extend a word into another, as stored tiles in alphabet order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hooks {
+    /// Letters that form a word when placed before the word
+    pub front: String,
+    /// Letters that form a word when placed after the word
+    pub back: String,
+    /// Whether the word without its first letter is a word
+    pub front_inner: bool,
+    /// Whether the word without its last letter is a word
+    pub back_inner: bool,
+}
+
+impl<'a> Dawg<'a> {
+    /// The front, back and inner hooks of `word`, which needn't be a word itself
+    pub fn hooks(&self, word: &str) -> Hooks {
+        let mut chars = word.chars();
+        chars.next();
+        let without_first = chars.as_str();
+        let mut chars = word.chars();
+        chars.next_back();
+        let without_last = chars.as_str();
+        Hooks {
+            front: CrossChecks::between(self, "", word).symbols(&self.alphabet),
+            back: CrossChecks::between(self, word, "").symbols(&self.alphabet),
+            front_inner: !without_first.is_empty() && self.contains(without_first),
+            back_inner: !without_last.is_empty() && self.contains(without_last),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::loading::load_dawg;
+
+    #[test]
+    fn test_hooks() {
+        let dawg = load_dawg();
+        assert_eq!(
+            dawg.hooks("care"),
+            Hooks {
+                front: "s".to_string(),
+                back: "drstx".to_string(),
+                front_inner: true,
+                back_inner: true,
+            }
+        );
+        let hooks = dawg.hooks("cwm");
+        assert_eq!((hooks.front.as_str(), hooks.back.as_str()), ("", "s"));
+        assert!(!hooks.front_inner && !hooks.back_inner);
+    }
+}
@@ -2,19 +2,41 @@
 #[macro_use]
 extern crate lazy_static;
 
-pub use crate::dawg::Dawg;
+pub use crate::alphabet::{Alphabet, AlphabetError};
+pub use crate::anagram::{Anagram, AnagramsByLength};
+pub use crate::building::DawgBuilder;
+pub use crate::dawg::{Dawg, DawgError};
+pub use crate::gaddag::Gaddag;
 pub use crate::game::{
-    Direction, PlayGenerator, Position, ScoredScrabblePlay, ScrabbleBoard, ScrabblePlay,
-    ScrabbleRack,
+    rank_moves, BoardLayout, Direction, EndgameSolution, EndgameSolver, Evaluator,
+    GaddagPlayGenerator, Game, GameError, LayoutError, LeaveError, LeaveHeuristic, LeaveTable,
+    Move, PlayBreakdown, PlayError, PlayGenerator, Player, Position, PreEndgameError,
+    PreEndgameMove, PreEndgameSolver, RankedMove, Ruleset, ScoreModifier, ScoreOnly, ScoredMove,
+    ScoredScrabblePlay, ScrabbleBoard, ScrabblePlay, ScrabbleRack, SimulatedMove,
+    SimulationSettings, Simulator, TileBag, TileSet, Turn, ValidatedPlay, WordScore,
+    MAX_PRE_ENDGAME_BAG,
 };
+pub use crate::header::{DawgMetadata, DAWG_FORMAT_VERSION};
+pub use crate::hooks::Hooks;
+#[cfg(any(test, feature = "embedded-lexicon"))]
 pub use crate::loading::load_dawg;
+pub use crate::pattern::{Matches, PatternError};
+pub use crate::words::Words;
 
+mod alphabet;
+mod anagram;
+mod building;
 mod dawg;
+mod gaddag;
 mod game;
+mod header;
+mod hooks;
 mod loading;
+mod pattern;
+mod words;
 
 pub fn board_from_contents(contents: &str) -> ScrabbleBoard {
-    ScrabbleBoard::from_contents(&contents).expect("Invalid input")
+    ScrabbleBoard::from_contents(contents).expect("Invalid input")
 }
 
 pub fn board_from_plays(plays: &Vec<ScrabblePlay>) -> ScrabbleBoard {
@@ -25,25 +47,88 @@ pub fn board_from_plays(plays: &Vec<ScrabblePlay>) -> ScrabbleBoard {
     board
 }
 
+/// The highest scoring plays for `rack_contents` on `board` using the words of `dawg`, such as
+/// the embedded lexicon from `load_dawg`, scored by English rules
 pub fn generate_plays(
+    dawg: &Dawg,
     rack_contents: &str,
     board: &ScrabbleBoard,
     max_n_plays: usize,
 ) -> Vec<ScoredScrabblePlay> {
-    let dawg = load_dawg();
-    let rack = ScrabbleRack::new(rack_contents);
-    let checked_board = board.to_checked_board(dawg);
+    generate_plays_by(dawg, rack_contents, board, max_n_plays, &ScoreOnly)
+}
+
+/// Like `generate_plays`, but ranked by their equity under `evaluator`, such as a `LeaveTable`
+/// or the `LeaveHeuristic`
+pub fn generate_plays_by<E: Evaluator + ?Sized>(
+    dawg: &Dawg,
+    rack_contents: &str,
+    board: &ScrabbleBoard,
+    max_n_plays: usize,
+    evaluator: &E,
+) -> Vec<ScoredScrabblePlay> {
+    let ruleset = Ruleset::english();
+    generate_plays_with_ruleset(dawg, &ruleset, rack_contents, board, max_n_plays, evaluator)
+}
+
+/// Like `generate_plays_by`, but scored by `ruleset` rather than English rules
+pub fn generate_plays_with_ruleset<E: Evaluator + ?Sized>(
+    dawg: &Dawg,
+    ruleset: &Ruleset,
+    rack_contents: &str,
+    board: &ScrabbleBoard,
+    max_n_plays: usize,
+    evaluator: &E,
+) -> Vec<ScoredScrabblePlay> {
+    let rack = ScrabbleRack::with_alphabet(rack_contents, &dawg.alphabet);
+    let checked_board = board.to_checked_board(dawg, &ruleset.tile_set);
     let generator = PlayGenerator {
         dawg,
+        ruleset,
         checked_board,
         rack,
     };
-    let mut plays = generator.plays();
-    plays.sort_by_key(|x| -x.score);
+    let moves = generator
+        .plays()
+        .into_iter()
+        .map(ScoredMove::from)
+        .collect();
+    let mut plays: Vec<ScoredScrabblePlay> = rank_moves(evaluator, board, &generator.rack, moves)
+        .into_iter()
+        .filter_map(|ranked| match ranked.scored.played {
+            Move::Place(play) => Some(ScoredScrabblePlay {
+                play,
+                score: ranked.scored.score,
+            }),
+            _ => None,
+        })
+        .collect();
     plays.truncate(max_n_plays);
     plays
 }
 
+/// Checks a submitted play against the words of `dawg` and English rules, returning its score
+/// and the words formed
+pub fn validate_play(
+    dawg: &Dawg,
+    board: &ScrabbleBoard,
+    rack: &ScrabbleRack,
+    play: &ScrabblePlay,
+) -> Result<ValidatedPlay, PlayError> {
+    validate_play_with_ruleset(dawg, &Ruleset::english(), board, rack, play)
+}
+
+/// Like `validate_play`, but scored by `ruleset` rather than English rules
+pub fn validate_play_with_ruleset(
+    dawg: &Dawg,
+    ruleset: &Ruleset,
+    board: &ScrabbleBoard,
+    rack: &ScrabbleRack,
+    play: &ScrabblePlay,
+) -> Result<ValidatedPlay, PlayError> {
+    board.validate_play(dawg, ruleset, rack, play)
+}
+
 #[cfg(test)]
 mod test {
     use crate::{Direction, Position, ScrabblePlay};
@@ -62,10 +147,146 @@ mod test {
     #[test]
     pub fn test_play_gen_2() {
         let best_play = best_play_for_test_board("abcde__");
-        assert_eq!(best_play.play.start, Position { row: 3, col: 10 });
+        assert_eq!(best_play.play.start, Position { row: 4, col: 10 });
         assert_eq!(best_play.play.direction, Direction::Vertical);
-        assert_eq!(best_play.play.word, "becalmed");
-        assert_eq!(best_play.score, 110);
+        assert_eq!(best_play.play.word, "baRledUc");
+        assert_eq!(best_play.score, 94);
+    }
+
+    #[test]
+    pub fn test_play_gen_blank() {
+        let plays = generate_plays(load_dawg(), "_", &get_test_board(), 1000);
+        assert!(!plays.is_empty());
+        for scored_play in plays {
+            let word = &scored_play.play.word;
+            assert_eq!(word.chars().filter(|ch| ch.is_uppercase()).count(), 1);
+        }
+    }
+
+    #[test]
+    pub fn test_play_gen_blank_for_held_letter() {
+        let dawg = Dawg::from_word_list(Alphabet::english(), ["hello", "hellos"]).unwrap();
+        let plays = generate_plays(&dawg, "s_", &get_test_board(), 1000);
+        let score = |word: &str| {
+            plays
+                .iter()
+                .find(|scored| scored.play.word == word)
+                .map(|scored| scored.score)
+        };
+        // A blank can stand for a letter the rack holds, keeping the S for later
+        assert_eq!(score("helloS"), Some(score("hellos").unwrap() - 1));
+    }
+
+    #[test]
+    pub fn test_play_gen_super_board() {
+        let mut board = ScrabbleBoard::new(BoardLayout::super_scrabble());
+        board.add_play(&ScrabblePlay {
+            start: Position { row: 10, col: 10 },
+            direction: Direction::Horizontal,
+            word: "hello".to_string(),
+        });
+        let plays = generate_plays(load_dawg(), "abcdefg", &board, 1000);
+        assert!(plays
+            .iter()
+            .any(|scored| scored.play.positions().any(|position| position.col > 14)));
+    }
+
+    #[test]
+    pub fn test_play_gen_custom_dawg() {
+        let dawg = Dawg::from_word_list(Alphabet::english(), ["hello", "hellos", "so"]).unwrap();
+        let plays = generate_plays(&dawg, "s", &get_test_board(), 1000);
+        let words: Vec<&str> = plays
+            .iter()
+            .map(|scored| scored.play.word.as_str())
+            .collect();
+        assert_eq!(words, vec!["hellos", "so"]);
+    }
+
+    #[test]
+    pub fn test_play_gen_multibyte_letters() {
+        let dawg = Dawg::from_word_list(Alphabet::spanish(), ["año", "ño"]).unwrap();
+        let board = board_from_plays(&vec![ScrabblePlay {
+            start: Position { row: 7, col: 7 },
+            direction: Direction::Horizontal,
+            word: "o".to_string(),
+        }]);
+        let plays = generate_plays(&dawg, "añ", &board, 1000);
+        let start = |word: &str| {
+            plays
+                .iter()
+                .find(|scored| scored.play.word == word)
+                .map(|scored| scored.play.start)
+        };
+        assert_eq!(start("ño"), Some(Position { row: 7, col: 6 }));
+        assert_eq!(start("año"), Some(Position { row: 7, col: 5 }));
+    }
+
+    #[test]
+    pub fn test_play_gen_with_ruleset() {
+        let dawg = Dawg::from_word_list(Alphabet::spanish(), ["año", "ño"]).unwrap();
+        let board = board_from_plays(&vec![ScrabblePlay {
+            start: Position { row: 7, col: 7 },
+            direction: Direction::Horizontal,
+            word: "o".to_string(),
+        }]);
+        let spanish = Ruleset {
+            tile_set: TileSet::spanish(),
+            ..Ruleset::english()
+        };
+        let english = generate_plays(&dawg, "ñ", &board, 1);
+        let plays = generate_plays_with_ruleset(&dawg, &spanish, "ñ", &board, 1, &ScoreOnly);
+        assert_eq!(plays[0].play, english[0].play);
+        assert_eq!(english[0].score, 1);
+        assert_eq!(plays[0].score, 9);
+
+        let rack = ScrabbleRack::with_alphabet("ñ", &dawg.alphabet);
+        let validated = validate_play_with_ruleset(&dawg, &spanish, &board, &rack, &plays[0].play);
+        assert_eq!(validated.unwrap().score, 9);
+        let validated = validate_play(&dawg, &board, &rack, &plays[0].play);
+        assert_eq!(validated.unwrap().score, 1);
+    }
+
+    #[test]
+    pub fn test_play_gen_empty_board() {
+        let board = ScrabbleBoard::default();
+        let plays = generate_plays(load_dawg(), "abcdefg", &board, 1000);
+        assert!(!plays.is_empty());
+        let start = board.layout.start();
+        for scored in plays.iter() {
+            assert_eq!(scored.play.direction, Direction::Horizontal);
+            assert!(scored.play.positions().any(|position| position == start));
+        }
+        let mut unique = plays.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), plays.len());
+
+        // Off the diagonal, the start square can be covered by plays in either direction
+        let layout_text = format!("{}start 7 3\n", "...............\n".repeat(15));
+        let board = ScrabbleBoard::new(BoardLayout::from_text(&layout_text).unwrap());
+        let plays = generate_plays(load_dawg(), "abcdefg", &board, 1000);
+        assert!(plays
+            .iter()
+            .any(|scored| scored.play.direction == Direction::Vertical));
+    }
+
+    #[test]
+    pub fn test_play_gen_by_equity() {
+        let board = get_test_board();
+        let heuristic = LeaveHeuristic::default();
+        let plays = generate_plays_by(load_dawg(), "abcdes_", &board, 1000, &heuristic);
+        let by_score = generate_plays(load_dawg(), "abcdes_", &board, 1000);
+        assert_eq!(plays.len(), by_score.len());
+
+        let rack = ScrabbleRack::new("abcdes_");
+        let equities: Vec<f64> = plays
+            .iter()
+            .map(|scored| {
+                let leave = ScoredMove::from(scored.clone()).leave(&board, &rack);
+                heuristic.equity(scored.score, &leave)
+            })
+            .collect();
+        assert!(equities.windows(2).all(|pair| pair[0] >= pair[1]));
     }
 
     fn best_play_for_test_board(rack_contents: &str) -> ScoredScrabblePlay {
@@ -84,8 +305,7 @@ mod test {
 
     fn best_play_for_board(rack_contents: &str, board: &ScrabbleBoard) -> ScoredScrabblePlay {
         let max_n_plays = 5;
-        let plays = generate_plays(rack_contents, board, max_n_plays);
-        dbg!(&plays);
+        let plays = generate_plays(load_dawg(), rack_contents, board, max_n_plays);
         assert_eq!(plays.len(), max_n_plays);
         plays[0].clone()
     }
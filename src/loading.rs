@@ -1,63 +1,76 @@
-use crate::dawg::{Dawg, DawgEdge};
-use std::mem::size_of;
-
-pub const A_INDEX: u8 = 97;
+#[cfg(any(test, feature = "embedded-lexicon"))]
+use crate::dawg::Dawg;
 
+#[cfg(any(test, feature = "embedded-lexicon"))]
 lazy_static! {
     // Use &*DAWG to access a global instance of the dawg
     // this makes it easier to hide this implementation detail in an externally-facing API
-    pub static ref DAWG: Dawg = parse_dawg();
+    pub static ref DAWG: Dawg<'static> = parse_dawg(dawg_bytes());
 }
 
-pub fn load_dawg() -> &'static Dawg {
-    &*DAWG
+/// The English lexicon built into the crate by the `embedded-lexicon` feature
+#[cfg(any(test, feature = "embedded-lexicon"))]
+pub fn load_dawg() -> &'static Dawg<'static> {
+    &DAWG
 }
 
-fn parse_dawg() -> Dawg {
-    // The following results in a smaller binary, but requires the file to be locally available
-    //use std::fs;
-    //let bytes = fs::read("assets/dawg.bin").expect("Couldn't load asserts/dawg.bin");
-    static DAWG_BYTES: &'static [u8] = include_bytes!("../assets/dawg.bin");
-    let bytes = DAWG_BYTES;
+#[cfg(feature = "embedded-lexicon")]
+fn dawg_bytes() -> &'static [u8] {
+    include_bytes!("../assets/dawg.bin")
+}
 
-    let u64_size = size_of::<u64>();
-    let n_dawg_edges = bytes.len() / u64_size;
-    let mut edges: Vec<DawgEdge> = Vec::with_capacity(n_dawg_edges);
-    for i in 0..n_dawg_edges {
-        let mut num: u64 = 0;
-        for j in 0..u64_size {
-            let byte_index = u64_size * i + j;
-            num += (bytes[byte_index] as u64) << (8 * j) as u64;
-        }
-        edges.push(num.into());
+// Without the embedded lexicon, tests read the same asset at runtime
+#[cfg(all(test, not(feature = "embedded-lexicon")))]
+fn dawg_bytes() -> &'static [u8] {
+    lazy_static! {
+        static ref DAWG_BYTES: Vec<u8> =
+            std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/dawg.bin"))
+                .expect("Couldn't load assets/dawg.bin");
     }
-    Dawg { edges }
+    &DAWG_BYTES
+}
+
+/// Borrows the lexicon's dawg file without checking its edges, which `test_embedded_dawg_is_valid`
+/// does once instead of every load
+#[cfg(any(test, feature = "embedded-lexicon"))]
+fn parse_dawg(bytes: &[u8]) -> Dawg<'_> {
+    Dawg::from_bytes_unchecked(bytes).expect("Invalid dawg")
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::dawg::DawgNodeIndex;
+    use crate::alphabet::{Alphabet, Letter};
+    use crate::dawg::{DawgEdge, DawgNodeIndex};
+
+    #[test]
+    fn test_embedded_dawg_is_valid() {
+        let checked = Dawg::from_bytes(dawg_bytes()).unwrap();
+        assert!(checked.edges().eq(load_dawg().edges()));
+    }
 
     #[test]
     fn test_load_dawg_data() {
-        let dawg_data = &load_dawg().edges;
-        assert_eq!(dawg_data.len(), 190446);
+        let dawg = load_dawg();
+        assert_eq!(dawg.n_edges(), 190446);
+        assert_eq!(dawg.alphabet, Alphabet::english());
+        assert_eq!(dawg.metadata.lexicon, "scrabble_words");
+        assert_eq!(dawg.metadata.word_count, 279496);
         assert_eq!(
-            dawg_data[0],
+            dawg.edge(DawgNodeIndex(0)),
             DawgEdge {
-                letter: 'a',
+                letter: Letter(0),
                 word_terminator: false,
                 target: DawgNodeIndex(26),
                 node_terminator: false,
             }
         );
         assert_eq!(
-            dawg_data[1000],
+            dawg.edge(DawgNodeIndex(1000)),
             DawgEdge {
-                letter: 'l',
+                letter: Letter(18),
                 word_terminator: false,
-                target: DawgNodeIndex(136),
+                target: DawgNodeIndex(7538),
                 node_terminator: false,
             }
         );
@@ -73,7 +86,7 @@ mod benches {
     #[bench]
     pub fn bench_parse_dawg(b: &mut test::Bencher) {
         let mut dawg: Option<Dawg> = None;
-        b.iter(|| dawg = Some(parse_dawg()));
+        b.iter(|| dawg = Some(parse_dawg(dawg_bytes())));
         // Actually the dawg to ensure the loading doesn't get compiled out; maybe this is unnecessary
         assert!(dawg.unwrap().contains("hello"))
     }
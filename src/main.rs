@@ -5,7 +5,7 @@ use scrabble::{board_from_plays, generate_plays, Direction, Position, ScrabblePl
 
 fn main() {
     let start = Instant::now();
-    load_dawg();
+    let dawg = load_dawg();
     let duration = start.elapsed();
     println!("Time elapsed in load_dawg() is: {:?}", duration);
 
@@ -18,7 +18,7 @@ fn main() {
     println!("{}", board.display());
 
     let start = Instant::now();
-    let plays = generate_plays("abcdefg", &board, 20);
+    let plays = generate_plays(dawg, "abcdefg", &board, 20);
     let duration = start.elapsed();
     for play in plays.iter() {
         println!("{:?}", play);
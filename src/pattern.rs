@@ -0,0 +1,276 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::alphabet::{Alphabet, Letter};
+use crate::dawg::{Dawg, DawgEdge};
+use crate::game::ScrabbleRack;
+use crate::words::{Walk, WalkFilter};
+
+/// One square of a pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PatternItem {
+    Letter(Letter),
+    /// `?`
+    Any,
+    /// `*`, any number of letters, including none
+    AnyRun,
+    /// `[...]`, one bit per letter as in cross checks
+    Class(u64),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternError {
+    /// The text from this point doesn't start with a tile of the alphabet
+    UnknownTile(String),
+    /// A `[` has no matching `]`
+    UnclosedClass,
+    /// A letter class has no letters
+    EmptyClass,
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PatternError::UnknownTile(text) => write!(f, "No tile matches '{}'", text),
+            PatternError::UnclosedClass => write!(f, "Letter class has no closing ']'"),
+            PatternError::EmptyClass => write!(f, "Letter class has no letters"),
+        }
+    }
+}
+
+impl Error for PatternError {}
+
+fn parse_pattern(alphabet: &Alphabet, pattern: &str) -> Result<Vec<PatternItem>, PatternError> {
+    let mut items = Vec::new();
+    let mut rest = pattern;
+    while let Some(ch) = rest.chars().next() {
+        let item = match ch {
+            '?' => PatternItem::Any,
+            '*' => PatternItem::AnyRun,
+            '[' => {
+                let end = rest.find(']').ok_or(PatternError::UnclosedClass)?;
+                let mut class = 0;
+                for letter in parse_letters(alphabet, &rest[1..end])? {
+                    class |= 1 << letter.index();
+                }
+                if class == 0 {
+                    return Err(PatternError::EmptyClass);
+                }
+                rest = &rest[end + 1..];
+                items.push(PatternItem::Class(class));
+                continue;
+            }
+            _ => {
+                let (tile, n_bytes) = next_letter(alphabet, rest)?;
+                rest = &rest[n_bytes..];
+                items.push(PatternItem::Letter(tile));
+                continue;
+            }
+        };
+        rest = &rest[ch.len_utf8()..];
+        // Consecutive runs match the same words as a single one
+        if !(item == PatternItem::AnyRun && items.last() == Some(&PatternItem::AnyRun)) {
+            items.push(item);
+        }
+    }
+    Ok(items)
+}
+
+fn parse_letters(alphabet: &Alphabet, text: &str) -> Result<Vec<Letter>, PatternError> {
+    let mut letters = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let (letter, n_bytes) = next_letter(alphabet, rest)?;
+        letters.push(letter);
+        rest = &rest[n_bytes..];
+    }
+    Ok(letters)
+}
+
+fn next_letter(alphabet: &Alphabet, text: &str) -> Result<(Letter, usize), PatternError> {
+    alphabet
+        .next_tile(text)
+        .and_then(|(tile, n_bytes)| Some((alphabet.letter(tile)?, n_bytes)))
+        .ok_or_else(|| PatternError::UnknownTile(text.to_string()))
+}
+
+/// A way through the pattern: the index of the next item to match, and what is left of the rack
+/// if wildcard letters have to come from one
+type Thread = (usize, Option<ScrabbleRack>);
+
+/// Walks the words matching a pattern, by the threads through it that match each prefix
+struct PatternFilter {
+    pattern: Vec<PatternItem>,
+}
+
+impl WalkFilter for PatternFilter {
+    type State = Vec<Thread>;
+
+    fn step(&self, threads: &Vec<Thread>, edge: &DawgEdge) -> Option<Vec<Thread>> {
+        let threads = step(&self.pattern, threads, edge.letter);
+        if threads.is_empty() {
+            None
+        } else {
+            Some(threads)
+        }
+    }
+
+    fn is_word(&self, threads: &Vec<Thread>, edge: &DawgEdge) -> bool {
+        let n_items = self.pattern.len();
+        edge.word_terminator && threads.iter().any(|&(index, _)| index == n_items)
+    }
+
+    fn can_extend(&self, threads: &Vec<Thread>) -> bool {
+        let n_items = self.pattern.len();
+        threads.iter().any(|&(index, _)| index < n_items)
+    }
+}
+
+/// The words matching a pattern, found lazily in the order of the dawg's letters
+pub struct Matches<'d, 'a> {
+    walk: Walk<'d, 'a, PatternFilter>,
+}
+
+impl<'a> Dawg<'a> {
+    /// The words matching `pattern`, in which `?` matches any letter, `*` any run of letters
+    /// and `[...]` any of the letters between the brackets. Other letters match themselves.
+    pub fn matching(&self, pattern: &str) -> Result<Matches<'_, 'a>, PatternError> {
+        self.matches(pattern, None)
+    }
+
+    /// Like `matching`, but every letter matched by `?`, `*` or a class has to be taken from
+    /// `rack`, with blanks standing for letters it doesn't have. The rack's tiles have to be in
+    /// this dawg's alphabet.
+    pub fn matching_with_rack(
+        &self,
+        pattern: &str,
+        rack: &ScrabbleRack,
+    ) -> Result<Matches<'_, 'a>, PatternError> {
+        let rack = ScrabbleRack::with_alphabet(&rack.tiles(), &self.alphabet);
+        self.matches(pattern, Some(rack))
+    }
+
+    fn matches(
+        &self,
+        pattern: &str,
+        rack: Option<ScrabbleRack>,
+    ) -> Result<Matches<'_, 'a>, PatternError> {
+        let pattern = parse_pattern(&self.alphabet, pattern)?;
+        let mut threads = Vec::new();
+        add_thread(&pattern, &mut threads, (0, rack));
+        let filter = PatternFilter { pattern };
+        Ok(Matches {
+            walk: Walk::new(self, filter, self.root(), String::new(), threads),
+        })
+    }
+}
+
+/// Adds `thread` to `threads`, along with the threads that skip the runs it is at
+fn add_thread(pattern: &[PatternItem], threads: &mut Vec<Thread>, thread: Thread) {
+    let (index, rack) = thread;
+    if pattern.get(index) == Some(&PatternItem::AnyRun) {
+        add_thread(pattern, threads, (index + 1, rack.clone()));
+    }
+    let thread = (index, rack);
+    if !threads.contains(&thread) {
+        threads.push(thread);
+    }
+}
+
+/// The threads that follow from matching `letter` in each of `threads`
+fn step(pattern: &[PatternItem], threads: &[Thread], letter: Letter) -> Vec<Thread> {
+    let mut next = Vec::new();
+    for (index, rack) in threads.iter() {
+        let (next_index, is_wildcard) = match pattern.get(*index) {
+            Some(&PatternItem::Letter(other)) if other == letter => (index + 1, false),
+            Some(PatternItem::Any) => (index + 1, true),
+            Some(PatternItem::AnyRun) => (*index, true),
+            Some(&PatternItem::Class(class)) if class & (1 << letter.index()) != 0 => {
+                (index + 1, true)
+            }
+            _ => continue,
+        };
+        let mut rack = rack.clone();
+        if is_wildcard {
+            if let Some(rack) = rack.as_mut() {
+                if !rack.remove_letter(letter) && !rack.remove_blank() {
+                    continue;
+                }
+            }
+        }
+        add_thread(pattern, &mut next, (next_index, rack));
+    }
+    next
+}
+
+impl<'d, 'a> Iterator for Matches<'d, 'a> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        self.walk.next()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::loading::load_dawg;
+
+    fn matching(pattern: &str) -> Vec<String> {
+        load_dawg().matching(pattern).unwrap().collect()
+    }
+
+    #[test]
+    fn test_matching() {
+        assert_eq!(matching("c?t"), vec!["cat", "cit", "cot", "cut"]);
+        assert_eq!(
+            matching("qu[aeiou]?"),
+            vec![
+                "quad", "quag", "quai", "quat", "quay", "quep", "quey", "quid", "quim", "quin",
+                "quip", "quit", "quiz", "quod", "quop"
+            ]
+        );
+        assert_eq!(matching("hello"), vec!["hello"]);
+        assert!(matching("hel").is_empty());
+
+        // A word matching several ways is only found once
+        let words = matching("*a*a*");
+        assert!(words.len() > 1000);
+        assert!(words.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(matching("**"), matching("*"));
+    }
+
+    #[test]
+    fn test_matching_with_rack() {
+        let dawg = load_dawg();
+        let words: Vec<String> = dawg
+            .matching_with_rack("*an", &ScrabbleRack::new("bt"))
+            .unwrap()
+            .collect();
+        assert_eq!(words, vec!["an", "ban", "tan"]);
+        let words: Vec<String> = dawg
+            .matching_with_rack("c?t", &ScrabbleRack::new("a_"))
+            .unwrap()
+            .collect();
+        assert_eq!(words, vec!["cat", "cit", "cot", "cut"]);
+        let words: Vec<String> = dawg
+            .matching_with_rack("c?t", &ScrabbleRack::new("ab"))
+            .unwrap()
+            .collect();
+        assert_eq!(words, vec!["cat"]);
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        let dawg = load_dawg();
+        assert_eq!(
+            dawg.matching("c[ae").err(),
+            Some(PatternError::UnclosedClass)
+        );
+        assert_eq!(dawg.matching("c[]t").err(), Some(PatternError::EmptyClass));
+        assert_eq!(
+            dawg.matching("c4t").err(),
+            Some(PatternError::UnknownTile("4t".to_string()))
+        );
+    }
+}
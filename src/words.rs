@@ -0,0 +1,240 @@
+use std::collections::BTreeMap;
+
+use crate::dawg::{Dawg, DawgEdge, DawgNodeIndex, NULL_DAWG_NODE_INDEX};
+
+/// Narrows the words found by a `Walk`, by a state carried along the path to each of them
+pub(crate) trait WalkFilter {
+    type State;
+
+    /// The state after following `edge`, or `None` to skip it and every word through it
+    fn step(&self, state: &Self::State, edge: &DawgEdge) -> Option<Self::State>;
+
+    /// Whether the word ending with `edge` is found
+    fn is_word(&self, _state: &Self::State, edge: &DawgEdge) -> bool {
+        edge.word_terminator
+    }
+
+    /// Whether any longer word could be found
+    fn can_extend(&self, _state: &Self::State) -> bool {
+        true
+    }
+}
+
+/// A depth first walk over the words of a dawg in the order of its letters
+pub(crate) struct Walk<'d, 'a, F: WalkFilter> {
+    dawg: &'d Dawg<'a>,
+    filter: F,
+    /// The next edge to try in each node along the current word, if any are left, and the
+    /// state on reaching the node
+    stack: Vec<(Option<DawgNodeIndex>, F::State)>,
+    word: String,
+}
+
+impl<'d, 'a, F: WalkFilter> Walk<'d, 'a, F> {
+    /// A walk over the words below `node`, each starting with `word`
+    pub(crate) fn new(
+        dawg: &'d Dawg<'a>,
+        filter: F,
+        node: DawgNodeIndex,
+        word: String,
+        state: F::State,
+    ) -> Walk<'d, 'a, F> {
+        let mut stack = Vec::new();
+        if (node.0 as usize) < dawg.n_edges() {
+            stack.push((Some(node), state));
+        }
+        Walk {
+            dawg,
+            filter,
+            stack,
+            word,
+        }
+    }
+}
+
+impl<'d, 'a, F: WalkFilter> Iterator for Walk<'d, 'a, F> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        loop {
+            let (next_edge, state) = self.stack.last_mut()?;
+            let edge_index = match *next_edge {
+                Some(edge_index) => edge_index,
+                None => {
+                    self.stack.pop();
+                    self.word.pop();
+                    continue;
+                }
+            };
+            let edge = self.dawg.edge(edge_index);
+            *next_edge = match edge.node_terminator {
+                true => None,
+                false => Some(DawgNodeIndex(edge_index.0 + 1)),
+            };
+            let state = match self.filter.step(state, &edge) {
+                Some(state) => state,
+                None => continue,
+            };
+            self.word.push(self.dawg.alphabet.symbol(edge.letter));
+            let found = if self.filter.is_word(&state, &edge) {
+                Some(self.word.clone())
+            } else {
+                None
+            };
+            if edge.target.is_some() && self.filter.can_extend(&state) {
+                self.stack.push((Some(edge.target), state));
+            } else {
+                self.word.pop();
+            }
+            if found.is_some() {
+                return found;
+            }
+        }
+    }
+}
+
+/// Finds every word
+pub(crate) struct AllWords;
+
+impl WalkFilter for AllWords {
+    type State = ();
+
+    fn step(&self, _state: &(), _edge: &DawgEdge) -> Option<()> {
+        Some(())
+    }
+}
+
+/// The words of a dawg, found lazily in the order of its letters
+pub struct Words<'d, 'a> {
+    walk: Walk<'d, 'a, AllWords>,
+    /// A word found before iterating, i.e. the prefix given to `words_with_prefix`
+    first: Option<String>,
+}
+
+impl<'a> Dawg<'a> {
+    /// Every word, as stored tiles
+    pub fn words(&self) -> Words<'_, 'a> {
+        self.words_with_prefix("")
+    }
+
+    /// The words starting with the tiles of `prefix`, including `prefix` itself
+    pub fn words_with_prefix(&self, prefix: &str) -> Words<'_, 'a> {
+        let mut first = None;
+        let node = if prefix.is_empty() {
+            self.root()
+        } else if let Some(edge) = self.walk_from_node(self.root(), prefix) {
+            if edge.word_terminator {
+                first = Some(prefix.to_string());
+            }
+            edge.target
+        } else {
+            DawgNodeIndex(NULL_DAWG_NODE_INDEX)
+        };
+        Words {
+            walk: Walk::new(self, AllWords, node, prefix.to_string(), ()),
+            first,
+        }
+    }
+
+    /// The number of words, counted without listing them
+    pub fn word_count(&self) -> usize {
+        let mut counts = vec![None; self.n_edges()];
+        self.count_below(self.root(), &mut counts)
+    }
+
+    /// The number of words of each length, in tiles
+    pub fn word_lengths(&self) -> BTreeMap<usize, usize> {
+        let mut lengths = BTreeMap::new();
+        for word in self.words() {
+            *lengths.entry(word.chars().count()).or_insert(0) += 1;
+        }
+        lengths
+    }
+
+    /// Counts the words below `node`, remembering the count for nodes shared by several words
+    fn count_below(&self, node: DawgNodeIndex, counts: &mut [Option<usize>]) -> usize {
+        if node.is_null() || counts.is_empty() {
+            return 0;
+        }
+        if let Some(count) = counts[node.0 as usize] {
+            return count;
+        }
+        let mut count = 0;
+        self.apply_to_child_edges(node, |edge| {
+            count += edge.word_terminator as usize + self.count_below(edge.target, counts);
+        });
+        counts[node.0 as usize] = Some(count);
+        count
+    }
+}
+
+impl<'d, 'a> Iterator for Words<'d, 'a> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        self.first.take().or_else(|| self.walk.next())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+    use crate::alphabet::Alphabet;
+    use crate::loading::load_dawg;
+
+    #[test]
+    fn test_words_match_source() {
+        let source = fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/assets/scrabble_words.txt"
+        ))
+        .unwrap();
+        let dawg = load_dawg();
+        assert!(dawg.words().eq(source.lines().map(str::to_string)));
+        assert_eq!(dawg.word_count(), source.lines().count());
+
+        let lengths = dawg.word_lengths();
+        assert_eq!(lengths[&2], 127);
+        assert_eq!(lengths.values().sum::<usize>(), dawg.word_count());
+    }
+
+    #[test]
+    fn test_words_with_prefix() {
+        let dawg = load_dawg();
+        let words: Vec<String> = dawg.words_with_prefix("hellb").collect();
+        assert_eq!(
+            words,
+            vec![
+                "hellbender",
+                "hellbenders",
+                "hellbent",
+                "hellbox",
+                "hellboxes",
+                "hellbroth",
+                "hellbroths"
+            ]
+        );
+        assert_eq!(
+            dawg.words_with_prefix("cwm").collect::<Vec<_>>(),
+            vec!["cwm", "cwms"]
+        );
+        assert_eq!(dawg.words_with_prefix("xq").count(), 0);
+    }
+
+    #[test]
+    fn test_multi_char_words() {
+        let alphabet = Alphabet::spanish();
+        let dawg = Dawg::from_word_list(alphabet.clone(), ["chorro", "carro", "ch"]).unwrap();
+        let words: Vec<String> = dawg.words().map(|word| alphabet.spell(&word)).collect();
+        assert_eq!(words, vec!["carro", "ch", "chorro"]);
+        assert_eq!(dawg.word_lengths()[&1], 1);
+        assert_eq!(
+            Dawg::from_word_list(alphabet, Vec::<&str>::new())
+                .unwrap()
+                .word_count(),
+            0
+        );
+    }
+}